@@ -0,0 +1,41 @@
+#![feature(test)]
+
+extern crate test;
+
+use ion::{Dictionary, Value};
+use test::{black_box, Bencher};
+
+fn nested_array() -> Value {
+    let row = |i: i64| {
+        let mut dict = Dictionary::new();
+        dict.insert("id".to_owned(), Value::Integer(i));
+        dict.insert("name".to_owned(), Value::String(format!("item \"{i}\"")));
+        dict.insert("price".to_owned(), Value::Float(i as f64 * 1.25));
+
+        Value::Array(vec![
+            Value::Dictionary(dict),
+            Value::Array(vec![Value::Boolean(i % 2 == 0), Value::Integer(i)]),
+        ])
+    };
+
+    Value::Array((0..1000).map(row).collect())
+}
+
+#[bench]
+fn to_string(bencher: &mut Bencher) {
+    let value = nested_array();
+
+    bencher.iter(|| black_box(value.to_string()))
+}
+
+#[bench]
+fn write_compact_reused_buffer(bencher: &mut Bencher) {
+    let value = nested_array();
+    let mut out = String::new();
+
+    bencher.iter(|| {
+        out.clear();
+        value.write_compact(&mut out);
+        black_box(out.len())
+    })
+}
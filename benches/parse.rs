@@ -54,6 +54,24 @@ mod parse {
         })
     }
 
+    #[bench]
+    fn section_on_start_of_ion_two_pass(bencher: &mut Bencher) {
+        bencher.iter(|| {
+            let result = Parser::new(DEF_HOTEL_ON_START).with_two_pass(true).read();
+
+            black_box(result.unwrap())
+        })
+    }
+
+    #[bench]
+    fn section_on_start_of_ion_single_pass(bencher: &mut Bencher) {
+        bencher.iter(|| {
+            let result = Parser::new(DEF_HOTEL_ON_START).read();
+
+            black_box(result.unwrap())
+        })
+    }
+
     #[bench]
     fn section_on_end_of_ion_tuned_parser(bencher: &mut Bencher) {
         bencher.iter(|| {
@@ -102,3 +120,65 @@ mod parse_filtered {
         })
     }
 }
+
+mod strings {
+    use super::*;
+
+    fn entries(value: &str) -> String {
+        (0..1000)
+            .map(|i| format!("key{i} = \"{value}\"\n"))
+            .collect()
+    }
+
+    #[bench]
+    fn without_escapes(bencher: &mut Bencher) {
+        let raw = entries("Double room with a sea view");
+
+        bencher.iter(|| black_box(Parser::new(&raw).read().unwrap()))
+    }
+
+    #[bench]
+    fn borrowed_without_escapes(bencher: &mut Bencher) {
+        let raw = entries("Double room with a sea view");
+
+        bencher.iter(|| black_box(Parser::new(&raw).read_borrowed().unwrap()))
+    }
+
+    #[bench]
+    fn with_escapes(bencher: &mut Bencher) {
+        let raw = entries(r#"Double room with a \"sea\" view"#);
+
+        bencher.iter(|| black_box(Parser::new(&raw).read().unwrap()))
+    }
+}
+
+mod cells {
+    use super::*;
+
+    fn rows(cell: &str) -> String {
+        (0..1000)
+            .map(|i| format!("| {i} | {cell} | {cell} |\n"))
+            .collect()
+    }
+
+    #[bench]
+    fn without_escapes(bencher: &mut Bencher) {
+        let raw = rows("Double room");
+
+        bencher.iter(|| black_box(Parser::new(&raw).read().unwrap()))
+    }
+
+    #[bench]
+    fn borrowed_without_escapes(bencher: &mut Bencher) {
+        let raw = rows("Double room");
+
+        bencher.iter(|| black_box(Parser::new(&raw).read_borrowed().unwrap()))
+    }
+
+    #[bench]
+    fn with_escapes(bencher: &mut Bencher) {
+        let raw = rows(r"Double \| room");
+
+        bencher.iter(|| black_box(Parser::new(&raw).read().unwrap()))
+    }
+}
@@ -0,0 +1,94 @@
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity((bytes.len() + 2) / 3 * 4);
+
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (chunk.get(1).copied().unwrap_or(0) as u32) << 8
+            | chunk.get(2).copied().unwrap_or(0) as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[((n >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+
+    out
+}
+
+pub(crate) fn decode(s: &str) -> Option<Vec<u8>> {
+    let s = s.as_bytes();
+
+    if s.len() % 4 != 0 {
+        return None;
+    }
+
+    let chunks = s.len() / 4;
+    let mut out = Vec::with_capacity(chunks * 3);
+
+    for (idx, chunk) in s.chunks(4).enumerate() {
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+
+        if padding > 2 || (padding > 0 && idx + 1 != chunks) {
+            return None;
+        }
+
+        let mut n = 0u32;
+
+        for &c in &chunk[..4 - padding] {
+            n = (n << 6) | sextet(c)? as u32;
+        }
+
+        n <<= 6 * padding as u32;
+
+        let bytes = [(n >> 16) as u8, (n >> 8) as u8, n as u8];
+        out.extend_from_slice(&bytes[..3 - padding]);
+    }
+
+    Some(out)
+}
+
+fn sextet(c: u8) -> Option<u8> {
+    match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode};
+
+    #[test]
+    fn encode_pads_output() {
+        assert_eq!("", encode(b""));
+        assert_eq!("SA==", encode(b"H"));
+        assert_eq!("SGU=", encode(b"He"));
+        assert_eq!("SGVsbG8=", encode(b"Hello"));
+        assert_eq!("SGVsbG8h", encode(b"Hello!"));
+    }
+
+    #[test]
+    fn decode_round_trips() {
+        for input in [&b""[..], b"H", b"He", b"Hello", b"Hello!", &[0, 255, 128]] {
+            assert_eq!(Some(input.to_vec()), decode(&encode(input)));
+        }
+    }
+
+    #[test]
+    fn decode_rejects_invalid_input() {
+        assert_eq!(None, decode("SGVsbG8"));
+        assert_eq!(None, decode("SG=sbG8="));
+        assert_eq!(None, decode("SA==SA=="));
+        assert_eq!(None, decode("S==="));
+        assert_eq!(None, decode("SGV*bG8="));
+    }
+}
@@ -1,15 +1,47 @@
+mod comment_target;
+mod conversion_error;
+#[cfg(feature = "serde")]
+mod deserialize;
 mod display;
+mod export_error;
 mod from_ion;
 mod from_row;
 mod ion_error;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "json")]
+mod jsonl;
+mod layout;
+mod markdown;
+mod pretty;
+mod row_shape_error;
 mod section;
+mod section_reader;
+#[cfg(feature = "serde")]
+mod serialize;
+mod snapshot;
+#[cfg(feature = "toml")]
+mod to_toml;
 mod value;
+mod value_ref;
+mod writer;
 
+pub use self::comment_target::*;
+pub use self::conversion_error::*;
+#[cfg(feature = "serde")]
+pub use self::deserialize::*;
+pub use self::export_error::*;
 pub use self::from_ion::*;
 pub use self::from_row::*;
 pub use self::ion_error::*;
+pub use self::layout::*;
+pub use self::pretty::*;
+pub use self::row_shape_error::*;
 pub use self::section::*;
+pub use self::section_reader::*;
 pub use self::value::*;
+pub use self::value_ref::*;
+pub use self::writer::*;
 use crate::Parser;
 use std::collections::BTreeMap;
 use std::str;
@@ -17,17 +49,27 @@ use std::str;
 #[derive(Debug)]
 pub struct Ion {
     sections: BTreeMap<String, Section>,
+    directives: Vec<(String, String)>,
+    section_arrays: BTreeMap<String, Vec<Section>>,
 }
 
 impl Ion {
     pub fn new(sections: BTreeMap<String, Section>) -> Ion {
-        Ion { sections }
+        Ion {
+            sections,
+            directives: Vec::new(),
+            section_arrays: BTreeMap::new(),
+        }
     }
 
     pub fn from_str_filtered(s: &str, accepted_sections: Vec<&str>) -> Result<Self, IonError> {
         parser_to_ion(Parser::new_filtered(s, accepted_sections))
     }
 
+    pub fn from_str_with_arrays(s: &str) -> Result<Self, IonError> {
+        parser_to_ion(Parser::new(s).with_array_sections(true))
+    }
+
     pub fn get(&self, key: &str) -> Option<&Section> {
         self.sections.get(key)
     }
@@ -37,6 +79,28 @@ impl Ion {
             .ok_or_else(|| IonError::MissingSection(key.to_owned()))
     }
 
+    pub fn require_sections(&self, names: &[&str]) -> Result<(), Vec<String>> {
+        let missing: Vec<_> = names
+            .iter()
+            .filter(|name| !self.sections.contains_key(**name))
+            .map(|name| (*name).to_owned())
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+
+    pub fn get_array(&self, key: &str) -> Option<&[Section]> {
+        self.section_arrays.get(key).map(Vec::as_slice)
+    }
+
+    pub fn directives(&self) -> &[(String, String)] {
+        &self.directives
+    }
+
     pub fn remove(&mut self, key: &str) -> Option<Section> {
         self.sections.remove(key)
     }
@@ -44,6 +108,28 @@ impl Ion {
     pub fn iter(&self) -> impl Iterator<Item = (&String, &Section)> {
         self.sections.iter()
     }
+
+    /// Number of rows, including headers, in all sections and array sections.
+    pub fn total_rows(&self) -> usize {
+        self.all_sections().map(|s| s.rows.len()).sum()
+    }
+
+    pub fn total_entries(&self) -> usize {
+        self.all_sections().map(|s| s.dictionary.len()).sum()
+    }
+
+    pub fn total_cells(&self) -> usize {
+        self.all_sections()
+            .flat_map(|s| &s.rows)
+            .map(Vec::len)
+            .sum()
+    }
+
+    fn all_sections(&self) -> impl Iterator<Item = &Section> {
+        self.sections
+            .values()
+            .chain(self.section_arrays.values().flatten())
+    }
 }
 
 impl str::FromStr for Ion {
@@ -56,7 +142,11 @@ impl str::FromStr for Ion {
 
 fn parser_to_ion(mut parser: Parser) -> Result<Ion, IonError> {
     match parser.read() {
-        Some(ion) => Ok(Ion::new(ion)),
+        Some(ion) => Ok(Ion {
+            sections: ion,
+            directives: parser.directives,
+            section_arrays: parser.section_arrays,
+        }),
         None => Err(IonError::ParserErrors(parser.errors)),
     }
 }
@@ -76,7 +166,25 @@ macro_rules! ion_filtered {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Ion, Value};
+    use crate::{Dictionary, Ion, Value};
+    use std::fs;
+
+    #[test]
+    fn totals() {
+        let ion: Ion = fs::read_to_string("tests/data/test.ion")
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        assert_eq!(7, ion.total_rows());
+        assert_eq!(6, ion.total_entries());
+        assert_eq!(13, ion.total_cells());
+
+        let ion = Ion::from_str_with_arrays("[[a]]\nx = 1\n| 1 | 2 |\n[[a]]\nx = 2\n").unwrap();
+        assert_eq!(1, ion.total_rows());
+        assert_eq!(2, ion.total_entries());
+        assert_eq!(2, ion.total_cells());
+    }
 
     #[test]
     fn as_string() {
@@ -94,6 +202,61 @@ mod tests {
 
         let v = Value::Integer(1);
         assert_eq!(None, v.as_boolean());
+        assert_eq!(None, v.as_bool());
+        assert!(!v.is_boolean());
+
+        let v = Value::Boolean(false);
+        assert_eq!(Some(false), v.as_bool());
+        assert!(v.is_boolean());
+    }
+
+    #[test]
+    fn as_float() {
+        let v = Value::Float(1.5);
+        assert_eq!(Some(1.5), v.as_float());
+        assert!(v.is_float());
+
+        let v = Value::Integer(2);
+        assert_eq!(Some(2.0), v.as_float());
+        assert!(!v.is_float());
+
+        let v = Value::String("1.5".into());
+        assert_eq!(None, v.as_float());
+    }
+
+    #[test]
+    fn parse_integral_float() {
+        let v = Value::Float(4.0);
+        assert_eq!("4", v.to_string());
+        assert_eq!(Ok(4), v.parse::<i64>());
+
+        let mut section = crate::Section::new();
+        section.rows.push(vec![v]);
+        assert_eq!(Ok(4), section.cell_as::<i64>(0, 0));
+    }
+
+    #[test]
+    fn as_array() {
+        let v = Value::Array(vec![Value::Integer(1)]);
+        assert_eq!(Some(&[Value::Integer(1)][..]), v.as_array());
+        assert!(v.is_array());
+
+        let v = Value::Integer(1);
+        assert_eq!(None, v.as_array());
+        assert!(!v.is_array());
+    }
+
+    #[test]
+    fn as_dictionary() {
+        let mut dict = Dictionary::new();
+        dict.insert("a".to_owned(), Value::Integer(1));
+        let v = Value::Dictionary(dict.clone());
+        assert_eq!(Some(&dict), v.as_dictionary());
+        assert!(v.is_dictionary());
+
+        let v = Value::Array(vec![]);
+        assert_eq!(None, v.as_dictionary());
+        assert!(!v.is_dictionary());
     }
 
     #[test]
@@ -101,8 +264,11 @@ mod tests {
         let v = Value::Integer(1);
         assert_eq!(Some(1), v.as_integer());
 
+        assert!(v.is_integer());
+
         let v = Value::String("foo".into());
         assert_eq!(None, v.as_integer());
+        assert!(!v.is_integer());
     }
 
     #[test]
@@ -159,6 +325,67 @@ mod tests {
         assert_eq!(0, rows.len());
     }
 
+    #[test]
+    fn require_sections() {
+        let ion = ion!(
+            r#"
+            [FOO]
+            [BAR]
+        "#
+        );
+
+        assert_eq!(Ok(()), ion.require_sections(&["FOO", "BAR"]));
+        assert_eq!(Ok(()), ion.require_sections(&[]));
+        assert_eq!(
+            Err(vec!["BAZ".to_owned(), "QUX".to_owned()]),
+            ion.require_sections(&["BAZ", "FOO", "QUX"])
+        );
+    }
+
+    #[test]
+    fn directives() {
+        let ion = ion!(
+            r#"
+            %version 2
+            [FOO]
+            a = 1
+            %unknown x y
+        "#
+        );
+
+        assert_eq!(
+            &[
+                ("version".to_owned(), "2".to_owned()),
+                ("unknown".to_owned(), "x y".to_owned())
+            ],
+            ion.directives()
+        );
+        assert_eq!(Some(&Value::Integer(1)), ion.get("FOO").unwrap().get("a"));
+    }
+
+    #[test]
+    fn array_sections() {
+        let raw = r#"
+            [[item]]
+            id = 1
+            [[item]]
+            id = 2
+        "#;
+        let ion = Ion::from_str_with_arrays(raw).unwrap();
+
+        let items = ion.get_array("item").unwrap();
+        assert_eq!(2, items.len());
+        assert_eq!(Some(&Value::Integer(2)), items[1].get("id"));
+        assert!(ion.get("item").is_none());
+        assert_eq!("[[item]]\nid = 1\n\n[[item]]\nid = 2\n\n", ion.to_string());
+        assert_eq!(
+            ion.to_string(),
+            Ion::from_str_with_arrays(&ion.to_string())
+                .unwrap()
+                .to_string()
+        );
+    }
+
     #[test]
     fn filtered_section() {
         let ion = ion_filtered!(
@@ -0,0 +1,17 @@
+/// The element a comment is attached to by
+/// [`Parser::read_with_attached_comments`](crate::Parser::read_with_attached_comments).
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CommentTarget {
+    Section(String),
+    Entry {
+        section: String,
+        key: String,
+    },
+    /// `index` counts the rows of the section, including headers.
+    Row {
+        section: String,
+        index: usize,
+    },
+    /// Comments after the last element.
+    End,
+}
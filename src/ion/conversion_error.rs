@@ -0,0 +1,19 @@
+use std::{error, fmt};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConversionError {
+    OutOfBounds { row: usize, col: usize },
+    InvalidValue(String),
+}
+
+impl error::Error for ConversionError {
+    fn description(&self) -> &str {
+        "ConversionError"
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
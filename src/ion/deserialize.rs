@@ -0,0 +1,298 @@
+use crate::{Dictionary, Ion, IonError, Value};
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use serde::{Deserialize, Deserializer};
+use std::collections::btree_map;
+use std::{fmt, slice};
+
+/// Deserializes an Ion document into `T`. A document without sections is
+/// read from its root entries; otherwise it is a map from section name to
+/// the section's entries. Table rows are not deserialized.
+pub fn from_str<T: DeserializeOwned>(s: &str) -> Result<T, IonError> {
+    let mut ion: Ion = s.parse()?;
+
+    let value = match ion.sections.remove("root") {
+        Some(root) if ion.sections.is_empty() => Value::Dictionary(root.dictionary),
+        root => Value::Dictionary(
+            ion.sections
+                .into_iter()
+                .chain(root.map(|root| ("root".to_owned(), root)))
+                .map(|(name, section)| (name, Value::Dictionary(section.dictionary)))
+                .collect(),
+        ),
+    };
+
+    T::deserialize(&value)
+}
+
+impl de::Error for IonError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        IonError::DeserializeError(msg.to_string())
+    }
+}
+
+/// Visits strings, integers, floats, booleans, sequences and maps as the
+/// matching `Value` variant. `Null` and unresolved `Lazy` values are visited
+/// as unit.
+impl<'de> Deserializer<'de> for &'de Value {
+    type Error = IonError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IonError> {
+        match self {
+            Value::String(v) => visitor.visit_borrowed_str(v),
+            Value::Integer(v) => visitor.visit_i64(*v),
+            Value::Float(v) => visitor.visit_f64(*v),
+            Value::Boolean(v) => visitor.visit_bool(*v),
+            Value::Array(v) => visitor.visit_seq(SeqDeserializer(v.iter())),
+            Value::Dictionary(v) => visitor.visit_map(MapDeserializer {
+                entries: v.iter(),
+                value: None,
+            }),
+            Value::Bytes(v) => visitor.visit_borrowed_bytes(v),
+            Value::Lazy(_) | Value::Null => visitor.visit_unit(),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, IonError> {
+        match self {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, IonError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct enum
+        identifier ignored_any
+    }
+}
+
+struct SeqDeserializer<'de>(slice::Iter<'de, Value>);
+
+impl<'de> SeqAccess<'de> for SeqDeserializer<'de> {
+    type Error = IonError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, IonError> {
+        self.0
+            .next()
+            .map(|value| seed.deserialize(value))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct MapDeserializer<'de> {
+    entries: btree_map::Iter<'de, String, Value>,
+    value: Option<&'de Value>,
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer<'de> {
+    type Error = IonError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, IonError> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key.as_str().into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, IonError> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(value),
+            None => Err(de::Error::custom("value is missing")),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("an ion value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Boolean(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Integer(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Value, E> {
+        Ok(i64::try_from(v).map_or(Value::Float(v as f64), Value::Integer))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::Float(v))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::new_string(v))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Value, E> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut row = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+
+        while let Some(value) = seq.next_element()? {
+            row.push(value);
+        }
+
+        Ok(Value::Array(row))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut dictionary = Dictionary::new();
+
+        while let Some((key, value)) = map.next_key()?.map(|key| (key, map.next_value())) {
+            dictionary.insert(key, value?);
+        }
+
+        Ok(Value::Dictionary(dictionary))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{from_str, Dictionary, IonError, Value};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Config {
+        first: String,
+        some_bool: bool,
+        count: i64,
+        ratio: f64,
+        tags: Vec<String>,
+        limits: Limits,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Limits {
+        max: u32,
+    }
+
+    #[test]
+    fn root_entries() {
+        let raw = r#"
+            first = "hello"
+            some_bool = true
+            count = 3
+            ratio = 2
+            tags = ["a", "b"]
+            limits = { max = 10 }
+            ignored = 1
+        "#;
+
+        assert_eq!(
+            Config {
+                first: "hello".to_owned(),
+                some_bool: true,
+                count: 3,
+                ratio: 2.0,
+                tags: vec!["a".to_owned(), "b".to_owned()],
+                limits: Limits { max: 10 },
+            },
+            from_str::<Config>(raw).unwrap()
+        );
+    }
+
+    #[test]
+    fn sections() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Document {
+            limits: Limits,
+            other: Dictionary,
+        }
+
+        let raw = "[limits]\nmax = 5\n| x |\n[other]\na = 1\n";
+        let document: Document = from_str(raw).unwrap();
+
+        assert_eq!(Limits { max: 5 }, document.limits);
+        assert_eq!(Some(&Value::Integer(1)), document.other.get("a"));
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            from_str::<Limits>("max = 5000000000\n"),
+            Err(IonError::DeserializeError(_))
+        ));
+        assert!(matches!(
+            from_str::<Limits>("min = 1\n"),
+            Err(IonError::DeserializeError(_))
+        ));
+        assert!(matches!(
+            from_str::<Limits>("max = ?\n"),
+            Err(IonError::ParserErrors(_))
+        ));
+    }
+
+    #[test]
+    fn value_round_trip() {
+        let mut dict = Dictionary::new();
+        dict.insert("km".to_owned(), Value::Float(4.1));
+        dict.insert("loc".to_owned(), Value::new_string_array("M"));
+        let value = Value::Array(vec![
+            Value::Integer(1),
+            Value::Boolean(true),
+            Value::Null,
+            Value::Dictionary(dict),
+        ]);
+
+        assert_eq!(value, Value::deserialize(&value).unwrap());
+    }
+}
@@ -1,5 +1,5 @@
-use crate::{Ion, Section, Value};
-use std::fmt;
+use crate::{base64, Ion, Section, Value};
+use std::fmt::{self, Write};
 
 impl fmt::Display for Ion {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
@@ -9,6 +9,14 @@ impl fmt::Display for Ion {
             f.write_str("\n")?;
         }
 
+        for (name, sections) in &self.section_arrays {
+            for section in sections {
+                f.write_fmt(format_args!("[[{name}]]\n"))?;
+                section.fmt(f)?;
+                f.write_str("\n")?;
+            }
+        }
+
         Ok(())
     }
 }
@@ -25,7 +33,10 @@ impl fmt::Display for Section {
 
         for row in &self.rows {
             for cell in row {
-                fmt::Display::fmt(&format!("| {cell} "), f)?;
+                let mut out = String::from("| ");
+                cell.write_cell(&mut out, Floats::Display);
+                out.push(' ');
+                fmt::Display::fmt(&out, f)?;
             }
             f.write_str("|\n")?;
         }
@@ -36,74 +47,219 @@ impl fmt::Display for Section {
 
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let mut out = String::new();
+        self.write_compact(&mut out);
+        f.write_str(&out)
+    }
+}
+
+/// How floats are written by the compact writers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Floats {
+    /// Like `f64`'s `Display`, e.g. `2` for `2.0`.
+    Display,
+    /// Like `f64`'s `Debug`, which keeps the `.0` of integral floats and uses
+    /// an exponent for very large and small ones, so they read back as floats.
+    RoundTrip,
+}
+
+impl Value {
+    pub fn write_compact(&self, out: &mut String) {
+        self.write_compact_with(out, Floats::Display)
+    }
+
+    pub(crate) fn write_compact_with(&self, out: &mut String, floats: Floats) {
         match self {
-            Value::String(v) => {
-                for c in v.chars() {
-                    match c {
-                        '\\' => write!(f, "\\\\")?,
-                        '\n' => write!(f, "\\n")?,
-                        '\"' => write!(f, "\\\"")?,
-                        c => write!(f, "{c}")?,
-                    }
-                }
+            Value::String(v) => escape_into(out, v, '"'),
 
-                Ok(())
+            Value::Integer(v) => {
+                let _ = write!(out, "{v}");
+            }
+            Value::Float(v) if v.is_nan() => out.push_str("nan"),
+            Value::Float(v) if floats == Floats::RoundTrip => {
+                let _ = write!(out, "{v:?}");
+            }
+            Value::Float(v) => {
+                let _ = write!(out, "{v}");
+            }
+            Value::Boolean(v) => out.push_str(if *v { "true" } else { "false" }),
+            Value::Lazy(v) => {
+                let _ = write!(out, "lazy({}..{})", v.start, v.end);
+            }
+            Value::Null => out.push_str("null"),
+            Value::Bytes(v) => {
+                out.push_str("b64\"");
+                out.push_str(&base64::encode(v));
+                out.push('"');
             }
-
-            Value::Integer(v) => v.fmt(f),
-            Value::Float(v) => v.fmt(f),
-            Value::Boolean(v) => v.fmt(f),
 
             Value::Array(v) => {
-                f.write_str("[ ")?;
+                out.push_str("[ ");
 
-                let mut first = true;
-
-                for i in v {
-                    if first {
-                        first = false;
-                    } else {
-                        f.write_str(", ")?
+                for (idx, i) in v.iter().enumerate() {
+                    if idx > 0 {
+                        out.push_str(", ");
                     }
 
-                    if i.is_string() {
-                        f.write_str("\"")?;
-                        i.fmt(f)?;
-                        f.write_str("\"")?;
-                    } else {
-                        i.fmt(f)?;
-                    }
+                    i.write_compact_quoted(out, floats);
                 }
 
-                f.write_str(" ]")
+                out.push_str(" ]");
             }
 
             Value::Dictionary(d) => {
-                f.write_str("{ ")?;
+                out.push_str("{ ");
+
+                for (idx, (k, v)) in d.iter().enumerate() {
+                    if idx > 0 {
+                        out.push_str(", ");
+                    }
+
+                    out.push_str(k);
+                    out.push_str(" = ");
+                    v.write_compact_quoted(out, floats);
+                }
+
+                out.push_str(" }");
+            }
+        }
+    }
+
+    /// Like [`Value::to_string`], but without optional whitespace, e.g.
+    /// `[1,"foo"]` and `{foo="bar"}`.
+    pub fn to_minimal_string(&self) -> String {
+        let mut out = String::new();
+
+        match self {
+            Value::Array(_) | Value::Dictionary(_) => self.write_minimal(&mut out, Floats::Display),
+            _ => self.write_compact(&mut out),
+        }
 
-                let mut first = true;
+        out
+    }
 
-                for (k, v) in d {
-                    if first {
-                        first = false;
-                    } else {
-                        f.write_str(", ")?
+    pub(crate) fn write_minimal(&self, out: &mut String, floats: Floats) {
+        match self {
+            Value::Array(v) => {
+                out.push('[');
+
+                for (idx, i) in v.iter().enumerate() {
+                    if idx > 0 {
+                        out.push(',');
                     }
 
-                    k.fmt(f)?;
-                    f.write_str(" = ")?;
+                    i.write_minimal(out, floats);
+                }
+
+                out.push(']');
+            }
+
+            Value::Dictionary(d) => {
+                out.push('{');
 
-                    if v.type_str() == "string" {
-                        f.write_str("\"")?;
-                        v.fmt(f)?;
-                        f.write_str("\"")?;
-                    } else {
-                        v.fmt(f)?;
+                for (idx, (k, v)) in d.iter().enumerate() {
+                    if idx > 0 {
+                        out.push(',');
                     }
+
+                    out.push_str(k);
+                    out.push('=');
+                    v.write_minimal(out, floats);
                 }
 
-                f.write_str(" }")
+                out.push('}');
+            }
+
+            _ => self.write_compact_quoted(out, floats),
+        }
+    }
+
+    /// Writes a table cell, escaping `|` instead of `"` in strings.
+    pub(crate) fn write_cell(&self, out: &mut String, floats: Floats) {
+        match self {
+            Value::String(v) => escape_into(out, v, '|'),
+            _ => self.write_compact_with(out, floats),
+        }
+    }
+
+    pub(crate) fn write_compact_quoted(&self, out: &mut String, floats: Floats) {
+        if self.is_string() {
+            out.push('"');
+            self.write_compact_with(out, floats);
+            out.push('"');
+        } else {
+            self.write_compact_with(out, floats);
+        }
+    }
+}
+
+/// Escapes `s` so that the parser reads it back unchanged between
+/// `delimiter`s, `"` for strings and `|` for table cells.
+fn escape_into(out: &mut String, s: &str, delimiter: char) {
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if c == delimiter => {
+                out.push('\\');
+                out.push(c);
             }
+            c => out.push(c),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Floats;
+    use crate::{Dictionary, Parser, Section, Value};
+
+    #[test]
+    fn escapes_read_back() {
+        let raw = "a\"b\\c\nd\te\rf|g";
+        let value = Value::new_string(raw);
+        let mut section = Section::new();
+        section.dictionary.insert("s".to_owned(), value.clone());
+        section.rows.push(vec![value.clone()]);
+
+        let parsed = Parser::new(&section.to_string()).read().unwrap();
+        assert_eq!(Some(&value), parsed["root"].get("s"));
+        assert_eq!(value, parsed["root"].rows[0][0]);
+
+        let mut out = String::new();
+        value.write_cell(&mut out, Floats::Display);
+        assert_eq!("a\"b\\\\c\\nd\\te\\rf\\|g", out);
+        assert_eq!("a\\\"b\\\\c\\nd\\te\\rf|g", value.to_string());
+    }
+
+    #[test]
+    fn to_minimal_string() {
+        let mut dict = Dictionary::new();
+        dict.insert("foo".to_owned(), Value::new_string("b\"ar"));
+        dict.insert("e".to_owned(), Value::Array(vec![]));
+        let value = Value::Array(vec![
+            Value::Integer(1),
+            Value::new_string("foo"),
+            Value::Dictionary(dict),
+        ]);
+
+        assert_eq!(
+            "[1,\"foo\",{e=[],foo=\"b\\\"ar\"}]",
+            value.to_minimal_string()
+        );
+        assert_eq!(
+            "[ 1, \"foo\", { e = [  ], foo = \"b\\\"ar\" } ]",
+            value.to_string()
+        );
+        assert_eq!("a b", Value::new_string("a b").to_minimal_string());
+    }
+
+    #[test]
+    fn write_compact_appends() {
+        let mut out = "x = ".to_owned();
+        Value::new_string_array("a").write_compact(&mut out);
+        assert_eq!("x = [ \"a\" ]", out);
+    }
+}
@@ -0,0 +1,19 @@
+use std::{error, fmt};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExportError {
+    MissingHeader,
+    UnnamedColumn { row: usize, col: usize },
+}
+
+impl error::Error for ExportError {
+    fn description(&self) -> &str {
+        "ExportError"
+    }
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
@@ -7,6 +7,7 @@ pub enum IonError {
     MissingValue(String),
     ParseError,
     ParserErrors(Vec<ParserError>),
+    DeserializeError(String),
 }
 
 impl error::Error for IonError {
@@ -0,0 +1,133 @@
+use crate::{base64, ConversionError, Ion, Section, Value};
+use serde_json::{Map, Number};
+
+impl Ion {
+    /// Converts the document to a JSON object of sections, each holding its
+    /// entries and, if it has any, its rows as an array of arrays under a
+    /// `rows` key. Comments, directives and array sections are dropped.
+    pub fn to_json(&self) -> Result<serde_json::Value, ConversionError> {
+        let mut document = Map::new();
+
+        for (name, section) in self.iter() {
+            document.insert(name.clone(), section.to_json()?);
+        }
+
+        Ok(serde_json::Value::Object(document))
+    }
+}
+
+impl Section {
+    /// Converts the section to a JSON object as described in
+    /// [`Ion::to_json`]. Fails if the section has rows and an entry named
+    /// `rows`.
+    pub fn to_json(&self) -> Result<serde_json::Value, ConversionError> {
+        let mut object: Map<_, _> = self
+            .dictionary
+            .iter()
+            .map(|(key, value)| (key.clone(), value.to_json()))
+            .collect();
+
+        if !self.rows.is_empty() {
+            if object.contains_key("rows") {
+                return Err(ConversionError::InvalidValue("rows".to_owned()));
+            }
+
+            let rows = self
+                .rows
+                .iter()
+                .map(|row| serde_json::Value::Array(row.iter().map(Value::to_json).collect()))
+                .collect();
+
+            object.insert("rows".to_owned(), serde_json::Value::Array(rows));
+        }
+
+        Ok(serde_json::Value::Object(object))
+    }
+}
+
+impl Value {
+    /// Converts the value to JSON. Bytes become base64 strings; non-finite
+    /// floats, `Null` and unresolved `Lazy` values become `null`.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::String(v) => serde_json::Value::String(v.clone()),
+            Value::Integer(v) => serde_json::Value::Number((*v).into()),
+            Value::Float(v) => {
+                Number::from_f64(*v).map_or(serde_json::Value::Null, serde_json::Value::Number)
+            }
+            Value::Boolean(v) => serde_json::Value::Bool(*v),
+            Value::Array(v) => serde_json::Value::Array(v.iter().map(Value::to_json).collect()),
+            Value::Dictionary(v) => {
+                serde_json::Value::Object(v.iter().map(|(k, v)| (k.clone(), v.to_json())).collect())
+            }
+            Value::Bytes(v) => serde_json::Value::String(base64::encode(v)),
+            Value::Lazy(_) | Value::Null => serde_json::Value::Null,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ion, ConversionError, Ion, Value};
+
+    #[test]
+    fn nested_dictionary() {
+        let ion = ion!(
+            r#"
+            R75042 = {
+            view = "SV"
+            loc  = ["M", "B"]
+            dist = { beach_km = 4.1 }
+        }"#
+        );
+
+        assert_eq!(
+            r#"{"root":{"R75042":{"dist":{"beach_km":4.1},"loc":["M","B"],"view":"SV"}}}"#,
+            ion.to_json().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn rows() {
+        let ion = ion!(
+            r#"
+            [FOO]
+            a = 1
+            | x | y |
+            | 2 |
+
+            [BAR]
+            b = true
+            "#
+        );
+
+        assert_eq!(
+            r#"{"BAR":{"b":true},"FOO":{"a":1,"rows":[["x","y"],["2"]]}}"#,
+            ion.to_json().unwrap().to_string()
+        );
+    }
+
+    #[test]
+    fn rows_entry() {
+        let ion = ion!(
+            r#"
+            [FOO]
+            rows = 1
+            | a |
+            "#
+        );
+
+        assert_eq!(
+            Err(ConversionError::InvalidValue("rows".to_owned())),
+            ion.to_json()
+        );
+    }
+
+    #[test]
+    fn value() {
+        assert_eq!(r#""a""#, Value::new_string("a").to_json().to_string());
+        assert_eq!("null", Value::Float(f64::NAN).to_json().to_string());
+        assert_eq!("null", Value::Null.to_json().to_string());
+        assert_eq!(r#""AQI=""#, Value::Bytes(vec![1, 2]).to_json().to_string());
+    }
+}
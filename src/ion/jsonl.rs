@@ -0,0 +1,104 @@
+use crate::{ExportError, Section};
+use serde_json::Map;
+
+impl Section {
+    /// Exports the data rows as JSON Lines, one object per row keyed by the
+    /// header cells. The section must have a header (a first row followed by
+    /// a `---` separator row). Cells keep their parsed type; short rows only
+    /// produce the columns they have.
+    pub fn to_jsonl(&self) -> Result<String, ExportError> {
+        let rows = self.rows_without_header();
+
+        if rows.len() == self.rows.len() {
+            return Err(ExportError::MissingHeader);
+        }
+
+        let header: Vec<String> = self.rows[0]
+            .iter()
+            .map(|cell| {
+                cell.as_str()
+                    .map_or_else(|| cell.to_string(), str::to_owned)
+            })
+            .collect();
+
+        let mut out = String::new();
+
+        for (row_idx, row) in rows.iter().enumerate() {
+            let mut object = Map::new();
+
+            for (col, cell) in row.iter().enumerate() {
+                let name = header
+                    .get(col)
+                    .ok_or(ExportError::UnnamedColumn { row: row_idx, col })?;
+                object.insert(name.clone(), cell.to_json());
+            }
+
+            out.push_str(&serde_json::Value::Object(object).to_string());
+            out.push('\n');
+        }
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ion, ExportError, Ion, Section};
+
+    #[test]
+    fn to_jsonl() {
+        let ion = ion!(
+            r#"
+            [FOO]
+            | code | name   |
+            |------|--------|
+            | RO   | Room   |
+            | BB   | Bed \| |
+            | HB   |
+            "#
+        );
+
+        let expected = concat!(
+            r#"{"code":"RO","name":"Room"}"#,
+            "\n",
+            r#"{"code":"BB","name":"Bed |"}"#,
+            "\n",
+            r#"{"code":"HB"}"#,
+            "\n",
+        );
+        assert_eq!(Ok(expected.to_owned()), ion.get("FOO").unwrap().to_jsonl());
+    }
+
+    #[test]
+    fn to_jsonl_without_header() {
+        let ion = ion!(
+            r#"
+            [FOO]
+            | RO | Room |
+            "#
+        );
+
+        assert_eq!(
+            Err(ExportError::MissingHeader),
+            ion.get("FOO").unwrap().to_jsonl()
+        );
+        assert_eq!(Err(ExportError::MissingHeader), Section::new().to_jsonl());
+    }
+
+    #[test]
+    fn to_jsonl_with_unnamed_column() {
+        let ion = ion!(
+            r#"
+            [FOO]
+            | code |
+            |------|
+            | RO   | Room |
+            "#
+        );
+
+        assert_eq!(
+            Err(ExportError::UnnamedColumn { row: 0, col: 1 }),
+            ion.get("FOO").unwrap().to_jsonl()
+        );
+    }
+}
@@ -0,0 +1,19 @@
+use std::collections::BTreeMap;
+
+/// Source layout recorded by [`Parser::read_with_layout`] and reproduced by
+/// [`Writer::with_layout`].
+///
+/// [`Parser::read_with_layout`]: crate::Parser::read_with_layout
+/// [`Writer::with_layout`]: crate::Writer::with_layout
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Layout {
+    pub sections: BTreeMap<String, SectionLayout>,
+    pub trailing_blank_lines: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SectionLayout {
+    pub blank_lines_before: usize,
+    /// Column of the `=` shared by every entry of the section, if any.
+    pub equals_column: Option<usize>,
+}
@@ -0,0 +1,125 @@
+use crate::{Ion, Section, Value};
+
+impl Ion {
+    /// Renders the document as GitHub-flavored Markdown: every section is a
+    /// heading, its entries a key/value table and its rows a table, using the
+    /// header row when the section has a `---` separator row. Comments are
+    /// not kept in an `Ion`, so they are not rendered.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+
+        for (name, section) in &self.sections {
+            write_section(&mut out, name, section);
+        }
+
+        for (name, sections) in &self.section_arrays {
+            for section in sections {
+                write_section(&mut out, name, section);
+            }
+        }
+
+        out
+    }
+}
+
+fn write_section(out: &mut String, name: &str, section: &Section) {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+
+    out.push_str("## ");
+    out.push_str(name);
+    out.push('\n');
+
+    if !section.dictionary.is_empty() {
+        out.push('\n');
+        write_row(out, ["Key", "Value"].iter().map(|s| s.to_string()));
+        write_row(out, ["---", "---"].iter().map(|s| s.to_string()));
+
+        for (key, value) in &section.dictionary {
+            write_row(out, [key.clone(), cell_text(value)].into_iter());
+        }
+    }
+
+    if section.rows.is_empty() {
+        return;
+    }
+
+    let data = section.rows_without_header();
+    let width = section.rows.iter().map(Vec::len).max().unwrap_or(0);
+    out.push('\n');
+
+    if data.len() < section.rows.len() {
+        write_row(out, padded(&section.rows[0], width));
+    } else {
+        write_row(out, (0..width).map(|_| String::new()));
+    }
+
+    write_row(out, (0..width).map(|_| "---".to_owned()));
+
+    for row in data {
+        write_row(out, padded(row, width));
+    }
+}
+
+fn padded(row: &[Value], width: usize) -> impl Iterator<Item = String> + '_ {
+    (0..width).map(move |idx| row.get(idx).map(cell_text).unwrap_or_default())
+}
+
+fn write_row(out: &mut String, cells: impl Iterator<Item = String>) {
+    out.push('|');
+
+    for cell in cells {
+        out.push(' ');
+        out.push_str(&cell);
+        out.push_str(" |");
+    }
+
+    out.push('\n');
+}
+
+fn cell_text(value: &Value) -> String {
+    value
+        .to_display_string()
+        .replace('|', "\\|")
+        .replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ion, Ion};
+    use std::fs;
+
+    #[test]
+    fn to_markdown() {
+        let ion = ion!(fs::read_to_string("tests/data/test.ion").unwrap());
+        let markdown = ion.to_markdown();
+
+        let expected = "## table\n\
+                        \n\
+                        | abc | def |\n\
+                        | --- | --- |\n\
+                        | one | two |\n\
+                        | 1 | 2 |\n\
+                        | 2 | 3 |\n";
+        assert!(markdown.contains(expected), "{markdown}");
+        assert!(markdown.contains("| bool | true |\n"), "{markdown}");
+    }
+
+    #[test]
+    fn to_markdown_without_header() {
+        let ion = ion!("[A]\nkey = \"a|b\"\n| x | y |\n| z |\n");
+
+        let expected = "## A\n\
+                        \n\
+                        | Key | Value |\n\
+                        | --- | --- |\n\
+                        | key | a\\|b |\n\
+                        \n\
+                        |  |  |\n\
+                        | --- | --- |\n\
+                        | x | y |\n\
+                        | z |  |\n";
+        assert_eq!(expected, ion.to_markdown());
+    }
+}
@@ -0,0 +1,209 @@
+use super::display::Floats;
+use crate::Value;
+
+#[derive(Clone, Debug)]
+pub struct PrettyOpts {
+    pub indent: usize,
+    pub multiline: bool,
+    pub trailing_comma: bool,
+}
+
+impl Default for PrettyOpts {
+    fn default() -> Self {
+        Self {
+            indent: 4,
+            multiline: true,
+            trailing_comma: false,
+        }
+    }
+}
+
+impl Value {
+    pub fn to_pretty_string(&self, opts: &PrettyOpts) -> String {
+        let mut out = String::new();
+        write_value(&mut out, self, &Style::from(opts), 0);
+        out
+    }
+}
+
+/// How [`write_value`] lays out values, for [`PrettyOpts`] as well as for
+/// [`Writer`](crate::Writer).
+pub(crate) struct Style {
+    pub indent: usize,
+    pub multiline_arrays: bool,
+    pub multiline_dictionaries: bool,
+    pub trailing_comma: bool,
+    /// Writes a space inside the brackets of single-line arrays.
+    pub array_padding: bool,
+    /// Writes the padding of empty single-line containers too, e.g. `[  ]`.
+    pub pad_empty: bool,
+    pub floats: Floats,
+}
+
+impl From<&PrettyOpts> for Style {
+    fn from(opts: &PrettyOpts) -> Self {
+        Self {
+            indent: opts.indent,
+            multiline_arrays: opts.multiline,
+            multiline_dictionaries: opts.multiline,
+            trailing_comma: opts.trailing_comma,
+            array_padding: true,
+            pad_empty: false,
+            floats: Floats::Display,
+        }
+    }
+}
+
+pub(crate) fn write_value(out: &mut String, value: &Value, style: &Style, depth: usize) {
+    match value {
+        Value::Array(items) => write_container(
+            out,
+            ('[', ']'),
+            items.iter().map(|v| (None, v)),
+            (style.multiline_arrays, style.array_padding),
+            style,
+            depth,
+        ),
+        Value::Dictionary(dict) => write_container(
+            out,
+            ('{', '}'),
+            dict.iter().map(|(k, v)| (Some(k.as_str()), v)),
+            (style.multiline_dictionaries, true),
+            style,
+            depth,
+        ),
+        _ => value.write_compact_quoted(out, style.floats),
+    }
+}
+
+fn write_container<'a>(
+    out: &mut String,
+    (open, close): (char, char),
+    items: impl ExactSizeIterator<Item = (Option<&'a str>, &'a Value)>,
+    (multiline, padding): (bool, bool),
+    style: &Style,
+    depth: usize,
+) {
+    let len = items.len();
+    let padding = if padding { " " } else { "" };
+
+    out.push(open);
+
+    if len == 0 {
+        if style.pad_empty {
+            out.push_str(padding);
+            out.push_str(padding);
+        }
+
+        out.push(close);
+        return;
+    }
+
+    if !multiline {
+        out.push_str(padding);
+    }
+
+    for (idx, (key, value)) in items.enumerate() {
+        if multiline {
+            out.push('\n');
+            push_indent(out, style.indent * (depth + 1));
+        } else if idx > 0 {
+            out.push(' ');
+        }
+
+        if let Some(key) = key {
+            out.push_str(key);
+            out.push_str(" = ");
+        }
+
+        write_value(out, value, style, depth + usize::from(multiline));
+
+        if idx + 1 < len || style.trailing_comma {
+            out.push(',');
+        }
+    }
+
+    if multiline {
+        out.push('\n');
+        push_indent(out, style.indent * depth);
+    } else {
+        out.push_str(padding);
+    }
+
+    out.push(close);
+}
+
+fn push_indent(out: &mut String, width: usize) {
+    out.extend(std::iter::repeat(' ').take(width));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Dictionary, PrettyOpts, Value};
+
+    fn nested() -> Value {
+        let mut inner = Dictionary::new();
+        inner.insert("beach_km".to_owned(), Value::Float(4.1));
+        inner.insert("empty".to_owned(), Value::Array(vec![]));
+
+        let mut dict = Dictionary::new();
+        dict.insert("dist".to_owned(), Value::Dictionary(inner));
+        dict.insert(
+            "loc".to_owned(),
+            Value::Array(vec![Value::new_string("M"), Value::Integer(2)]),
+        );
+        dict.insert("view".to_owned(), Value::new_string("S\"V"));
+        Value::Dictionary(dict)
+    }
+
+    #[test]
+    fn multiline() {
+        let expected = r#"{
+    dist = {
+        beach_km = 4.1,
+        empty = []
+    },
+    loc = [
+        "M",
+        2
+    ],
+    view = "S\"V"
+}"#;
+
+        assert_eq!(expected, nested().to_pretty_string(&PrettyOpts::default()));
+    }
+
+    #[test]
+    fn multiline_with_trailing_comma_and_indent() {
+        let opts = PrettyOpts {
+            indent: 2,
+            trailing_comma: true,
+            ..PrettyOpts::default()
+        };
+        let value = Value::Array(vec![Value::Integer(1), Value::new_string_array("a")]);
+
+        assert_eq!(
+            "[\n  1,\n  [\n    \"a\",\n  ],\n]",
+            value.to_pretty_string(&opts)
+        );
+    }
+
+    #[test]
+    fn single_line_matches_display() {
+        let opts = PrettyOpts {
+            multiline: false,
+            ..PrettyOpts::default()
+        };
+        let value = Value::Array(vec![Value::Integer(1), Value::new_string_array("a")]);
+
+        assert_eq!(value.to_string(), value.to_pretty_string(&opts));
+    }
+
+    #[test]
+    fn scalar() {
+        let opts = PrettyOpts::default();
+
+        assert_eq!("\"foo\"", Value::new_string("foo").to_pretty_string(&opts));
+        assert_eq!("1", Value::Integer(1).to_pretty_string(&opts));
+    }
+}
@@ -0,0 +1,22 @@
+use std::{error, fmt};
+
+/// Row `row` of a section has `len` cells instead of the `expected` cells of
+/// the first row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RowShapeError {
+    pub row: usize,
+    pub len: usize,
+    pub expected: usize,
+}
+
+impl error::Error for RowShapeError {
+    fn description(&self) -> &str {
+        "RowShapeError"
+    }
+}
+
+impl fmt::Display for RowShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
@@ -1,4 +1,7 @@
-use crate::{Dictionary, FromIon, IonError, Row, Value};
+use crate::{
+    ConversionError, Dictionary, FromIon, IonError, Row, RowShapeError, SectionReader, Value,
+};
+use std::str::FromStr;
 use std::vec;
 
 #[derive(Debug, Default, PartialEq)]
@@ -19,8 +22,13 @@ impl Section {
         }
     }
 
-    pub fn get(&self, name: &str) -> Option<&Value> {
-        self.dictionary.get(name)
+    /// Looks up an entry, descending into it when `path` has more
+    /// dot-separated segments (see [`Value::get`]).
+    pub fn get(&self, path: &str) -> Option<&Value> {
+        self.dictionary.get(path).or_else(|| {
+            let (key, rest) = path.split_once('.')?;
+            self.dictionary.get(key)?.get(rest)
+        })
     }
 
     pub fn fetch(&self, key: &str) -> Result<&Value, IonError> {
@@ -28,6 +36,36 @@ impl Section {
             .ok_or_else(|| IonError::MissingValue(key.to_owned()))
     }
 
+    pub fn reader(&self) -> SectionReader {
+        SectionReader::new(self)
+    }
+
+    /// Returns `rows[row][col]` if it exists. Header and `---` separator
+    /// rows are counted like any other row.
+    pub fn cell(&self, row: usize, col: usize) -> Option<&Value> {
+        self.rows.get(row)?.get(col)
+    }
+
+    /// Returns the position of column `name` in the header (first) row.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        self.rows
+            .first()?
+            .iter()
+            .position(|cell| cell.as_str() == Some(name))
+    }
+
+    pub fn cell_as<T: FromStr>(&self, row: usize, col: usize) -> Result<T, ConversionError> {
+        let value = self
+            .cell(row, col)
+            .ok_or(ConversionError::OutOfBounds { row, col })?;
+
+        value
+            .parse()
+            .map_err(|_| ConversionError::InvalidValue(value.to_string()))
+    }
+
+    /// Skips the first two rows when the second one is a `---` separator.
+    /// Without a separator there is no header and all rows are returned.
     pub fn rows_without_header(&self) -> &[Row] {
         if self.rows.len() > 1 {
             let row = &self.rows[1];
@@ -43,6 +81,117 @@ impl Section {
         &self.rows
     }
 
+    /// Returns the data rows (see [`Section::rows_without_header`]) in
+    /// column-major order, so a header without a `---` separator is
+    /// included. Rows shorter than the widest row are padded with empty
+    /// strings, the same value an empty cell parses to.
+    pub fn columns(&self) -> Vec<Vec<&Value>> {
+        static EMPTY: Value = Value::String(String::new());
+
+        let rows = self.rows_without_header();
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+        (0..width)
+            .map(|col| {
+                rows.iter()
+                    .map(|row| row.get(col).unwrap_or(&EMPTY))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Returns every data row as a dictionary keyed by the header (first)
+    /// row's cells, skipping the `---` separator row if present. Unlike
+    /// [`Section::rows_without_header`], the first row is always taken as
+    /// the header since it provides the keys. Cells beyond the header's
+    /// width are dropped and columns missing from a short row are absent
+    /// from its dictionary. With duplicate column names the rightmost cell
+    /// wins.
+    pub fn rows_as_dicts(&self) -> Vec<Dictionary> {
+        let Some(header) = self.rows.first() else {
+            return Vec::new();
+        };
+
+        let header: Vec<_> = header.iter().map(Value::to_display_string).collect();
+        let data = if self.rows_without_header().len() < self.rows.len() {
+            self.rows_without_header()
+        } else {
+            &self.rows[1..]
+        };
+
+        data.iter()
+            .map(|row| header.iter().cloned().zip(row.iter().cloned()).collect())
+            .collect()
+    }
+
+    /// Checks that every row has as many cells as the first one, reporting
+    /// the first row that does not. A section without rows is valid.
+    pub fn validate_rows(&self) -> Result<(), RowShapeError> {
+        let Some(expected) = self.rows.first().map(Vec::len) else {
+            return Ok(());
+        };
+
+        match self.rows.iter().position(|row| row.len() != expected) {
+            Some(row) => Err(RowShapeError {
+                row,
+                len: self.rows[row].len(),
+                expected,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Inner-joins the data rows of both sections on the given key columns.
+    /// Headers are skipped as in [`Section::rows_without_header`].
+    ///
+    /// Every pair of rows with equal keys produces one row made of the row
+    /// from `self` followed by the row from `other`, ordered by `self` rows
+    /// first. Rows too short to have the key column never match. The result
+    /// has no header and an empty dictionary.
+    pub fn join(&self, other: &Section, self_key: usize, other_key: usize) -> Section {
+        let mut joined = Section::new();
+
+        for row in self.rows_without_header() {
+            let key = match row.get(self_key) {
+                Some(key) => key,
+                None => continue,
+            };
+
+            for other_row in other.rows_without_header() {
+                if other_row.get(other_key) == Some(key) {
+                    joined
+                        .rows
+                        .push(row.iter().chain(other_row).cloned().collect());
+                }
+            }
+        }
+
+        joined
+    }
+
+    /// Splits off the last row as a summary (e.g. a totals row) when it
+    /// matches `predicate`. All other rows are returned unchanged.
+    pub fn split_summary_row(
+        &self,
+        predicate: impl Fn(&[Value]) -> bool,
+    ) -> (Vec<Row>, Option<Row>) {
+        match self.rows.split_last() {
+            Some((last, rest)) if predicate(last) => (rest.to_vec(), Some(last.clone())),
+            _ => (self.rows.clone(), None),
+        }
+    }
+
+    /// Applies [`Value::visit_mut`] to every dictionary value and every cell.
+    pub fn visit_values_mut(&mut self, f: &mut impl FnMut(&mut Value)) {
+        self.dictionary.values_mut().for_each(|v| v.visit_mut(f));
+        self.rows.iter_mut().flatten().for_each(|v| v.visit_mut(f));
+    }
+
+    pub fn fold_rows_into_dictionary(&mut self, key: &str) {
+        let rows = self.rows.drain(..).map(Value::Array).collect();
+        self.dictionary.insert(key.to_owned(), Value::Array(rows));
+    }
+
     pub fn parse<F: FromIon<Section>>(&self) -> Result<F, F::Err> {
         F::from_ion(self)
     }
@@ -114,7 +263,7 @@ impl IntoIterator for Section {
 
 #[cfg(test)]
 mod tests {
-    use crate::{ion, Ion, Section};
+    use crate::{ion, Dictionary, Ion, Section, Value};
     use quickcheck::TestResult;
     use quickcheck_macros::quickcheck;
     use regex::Regex;
@@ -205,9 +354,487 @@ mod tests {
         }
     }
 
+    mod visit_values_mut {
+        use super::*;
+
+        #[test]
+        fn it_visits_entries_and_cells() {
+            let mut ion = ion!(
+                r#"
+                [FOO]
+                a = " x "
+                b = { c = ["  y"] }
+                | z   |   |
+                "#
+            );
+
+            let mut section = ion.remove("FOO").unwrap();
+            section.visit_values_mut(&mut |v| {
+                if let Value::String(s) = v {
+                    *s = s.trim().to_owned();
+                }
+            });
+
+            assert_eq!(
+                "a = \"x\"\nb = { c = [ \"y\" ] }\n| z |  |\n",
+                section.to_string()
+            );
+        }
+    }
+
+    mod cell {
+        use super::*;
+        use crate::ConversionError;
+
+        fn section() -> Section {
+            let mut ion = ion!(
+                r#"
+                [FOO]
+                | code | price |
+                |------|-------|
+                | A    | 12    |
+                | B    | n/a   |
+                "#
+            );
+
+            ion.remove("FOO").unwrap()
+        }
+
+        #[test]
+        fn it_returns_cell_in_range() {
+            let section = section();
+
+            assert_eq!(Some(&Value::new_string("code")), section.cell(0, 0));
+            assert_eq!(Some(&Value::new_string("A")), section.cell(2, 0));
+            assert_eq!(Some(&Value::new_string("n/a")), section.cell(3, 1));
+            assert_eq!(Ok(12), section.cell_as::<u32>(2, 1));
+            assert_eq!(Ok("B".to_owned()), section.cell_as::<String>(3, 0));
+        }
+
+        #[test]
+        fn it_counts_rows_of_table_without_separator() {
+            let ion = ion!(
+                r#"
+                [FOO]
+                | code | price |
+                | A    | 12    |
+                "#
+            );
+            let section = ion.get("FOO").unwrap();
+
+            assert_eq!(Some(&section.rows[0][1]), section.cell(0, 1));
+            assert_eq!(Ok(12), section.cell_as::<u32>(1, 1));
+        }
+
+        #[test]
+        fn it_returns_none_out_of_range() {
+            let section = section();
+
+            assert_eq!(None, section.cell(4, 0));
+            assert_eq!(None, section.cell(2, 2));
+            assert_eq!(
+                Err(ConversionError::OutOfBounds { row: 2, col: 2 }),
+                section.cell_as::<u32>(2, 2)
+            );
+        }
+
+        #[test]
+        fn it_fails_on_invalid_value() {
+            assert_eq!(
+                Err(ConversionError::InvalidValue("n/a".to_owned())),
+                section().cell_as::<u32>(3, 1)
+            );
+        }
+    }
+
+    mod get {
+        use super::*;
+
+        fn section() -> Section {
+            let mut ion = ion!(
+                r#"
+                [FOO]
+                hotel = { name = "H", rooms = [{ code = "SGL" }, { code = "DBL" }] }
+                "#
+            );
+
+            let mut section = ion.remove("FOO").unwrap();
+            section
+                .dictionary
+                .insert("a.b".to_owned(), Value::Integer(1));
+            section
+        }
+
+        #[test]
+        fn it_returns_deep_values() {
+            let section = section();
+
+            assert_eq!(Some(&Value::new_string("H")), section.get("hotel.name"));
+            assert_eq!(
+                Some(&Value::new_string("DBL")),
+                section.get("hotel.rooms.1.code")
+            );
+            assert_eq!(Some(&Value::Integer(1)), section.get("a.b"));
+        }
+
+        #[test]
+        fn it_returns_none_for_missing_paths() {
+            let section = section();
+
+            assert_eq!(None, section.get("hotel.address"));
+            assert_eq!(None, section.get("hotel.rooms.2.code"));
+            assert_eq!(None, section.get("hotel.rooms.x"));
+            assert_eq!(None, section.get("hotel.name.first"));
+            assert_eq!(None, section.get("missing.name"));
+        }
+    }
+
+    mod fold_rows_into_dictionary {
+        use super::*;
+
+        #[test]
+        fn it_moves_rows_under_key() {
+            let mut ion = ion!(
+                r#"
+                [FOO]
+                a = 1
+                | 1 | 2 |
+                | 3 |
+                "#
+            );
+
+            let mut section = ion.remove("FOO").unwrap();
+            section.fold_rows_into_dictionary("_rows");
+
+            assert!(section.rows.is_empty());
+            assert_eq!(Some(&Value::Integer(1)), section.get("a"));
+            assert_eq!(
+                "[ [ \"1\", \"2\" ], [ \"3\" ] ]",
+                section.get("_rows").unwrap().to_string()
+            );
+        }
+    }
+
+    mod join {
+        use super::*;
+
+        fn rows(section: &Section) -> Vec<Vec<String>> {
+            section
+                .rows
+                .iter()
+                .map(|row| row.iter().map(|v| v.to_string()).collect())
+                .collect()
+        }
+
+        #[test]
+        fn it_joins_on_key_columns() {
+            let ion = ion!(
+                r#"
+                [HOTEL]
+                | code | name  |
+                |------|-------|
+                | H1   | Alpha |
+                | H2   | Beta  |
+                | H3   | Gamma |
+                |
+                [ROOM]
+                | room | hotel |
+                |------|-------|
+                | SGL  | H1    |
+                | DBL  | H1    |
+                | SGL  | H3    |
+                | TWN  | H4    |
+                "#
+            );
+
+            let joined = ion
+                .get("HOTEL")
+                .unwrap()
+                .join(ion.get("ROOM").unwrap(), 0, 1);
+
+            assert!(joined.dictionary.is_empty());
+            assert_eq!(
+                vec![
+                    vec!["H1", "Alpha", "SGL", "H1"],
+                    vec!["H1", "Alpha", "DBL", "H1"],
+                    vec!["H3", "Gamma", "SGL", "H3"],
+                ],
+                rows(&joined)
+            );
+        }
+
+        #[test]
+        fn it_returns_empty_section_without_matches() {
+            let ion = ion!(
+                r#"
+                [A]
+                | 1 | a |
+                [B]
+                | 2 | b |
+                "#
+            );
+
+            let joined = ion.get("A").unwrap().join(ion.get("B").unwrap(), 0, 0);
+            assert!(joined.rows.is_empty());
+        }
+
+        #[test]
+        fn it_joins_headers_without_separator() {
+            let ion = ion!(
+                r#"
+                [A]
+                | key | a |
+                | 1   | x |
+                [B]
+                | key | b |
+                | 1   | y |
+                "#
+            );
+
+            let joined = ion.get("A").unwrap().join(ion.get("B").unwrap(), 0, 0);
+            assert_eq!(
+                vec![vec!["key", "a", "key", "b"], vec!["1", "x", "1", "y"]],
+                rows(&joined)
+            );
+        }
+    }
+
+    mod column_index {
+        use super::*;
+
+        #[test]
+        fn it_finds_column_in_header() {
+            let ion = ion!(
+                r#"
+                [FOO]
+                | code | price |
+                |------|-------|
+                | RO   | 10    |
+                "#
+            );
+            let section = ion.get("FOO").unwrap();
+
+            assert_eq!(Some(0), section.column_index("code"));
+            assert_eq!(Some(1), section.column_index("price"));
+            assert_eq!(
+                Some(&Value::new_string("10")),
+                section.cell(2, section.column_index("price").unwrap())
+            );
+        }
+
+        #[test]
+        fn it_returns_none_for_absent_column() {
+            let ion = ion!(
+                r#"
+                [FOO]
+                | code |
+                |------|
+                | RO   |
+                "#
+            );
+
+            assert_eq!(None, ion.get("FOO").unwrap().column_index("RO"));
+            assert_eq!(None, Section::new().column_index("code"));
+        }
+    }
+
+    mod split_summary_row {
+        use super::*;
+
+        fn is_total(row: &[Value]) -> bool {
+            row.first().and_then(Value::as_str) == Some("TOTAL")
+        }
+
+        #[test]
+        fn it_splits_off_matching_last_row() {
+            let ion = ion!(
+                r#"
+                [FOO]
+                | name  | qty |
+                |-------|-----|
+                | a     | 1   |
+                | b     | 2   |
+                | TOTAL | 3   |
+                "#
+            );
+
+            let (rows, summary) = ion.get("FOO").unwrap().split_summary_row(is_total);
+            assert_eq!(4, rows.len());
+            assert_eq!(Some("b"), rows[3][0].as_str());
+            assert_eq!(Some("3"), summary.unwrap()[1].as_str());
+        }
+
+        #[test]
+        fn it_keeps_all_rows_without_matching_last_row() {
+            let ion = ion!(
+                r#"
+                [FOO]
+                | TOTAL | 3 |
+                | a     | 1 |
+                "#
+            );
+
+            let (rows, summary) = ion.get("FOO").unwrap().split_summary_row(is_total);
+            assert_eq!(ion.get("FOO").unwrap().rows, rows);
+            assert!(summary.is_none());
+        }
+
+        #[test]
+        fn it_returns_nothing_for_empty_section() {
+            let (rows, summary) = Section::new().split_summary_row(|_| true);
+            assert!(rows.is_empty());
+            assert!(summary.is_none());
+        }
+    }
+
+    mod columns {
+        use super::*;
+
+        #[test]
+        fn it_works_on_rectangular_table() {
+            let ion = ion!(
+                r#"
+                [FOO]
+                | a | b |
+                |---|---|
+                | 1 | 2 |
+                | 3 | 4 |
+                "#
+            );
+
+            let columns = ion.get("FOO").unwrap().columns();
+            let one = Value::new_string("1");
+            let two = Value::new_string("2");
+            let three = Value::new_string("3");
+            let four = Value::new_string("4");
+            assert_eq!(vec![vec![&one, &three], vec![&two, &four]], columns);
+        }
+
+        #[test]
+        fn it_pads_ragged_rows() {
+            let ion = ion!(
+                r#"
+                [FOO]
+                | 1 |
+                | 2 | 3 | 4 |
+                | 5 | 6 |
+                "#
+            );
+
+            let columns = ion.get("FOO").unwrap().columns();
+            let column = |col: usize| -> Vec<String> {
+                columns[col].iter().map(|v| v.to_string()).collect()
+            };
+            assert_eq!(3, columns.len());
+            assert_eq!(vec!["1", "2", "5"], column(0));
+            assert_eq!(vec!["", "3", "6"], column(1));
+            assert_eq!(vec!["", "4", ""], column(2));
+        }
+
+        #[test]
+        fn it_returns_nothing_for_empty_section() {
+            assert!(Section::new().columns().is_empty());
+        }
+    }
+
+    mod validate_rows {
+        use super::*;
+        use crate::RowShapeError;
+
+        #[test]
+        fn it_accepts_rectangular_table() {
+            let ion = ion!(
+                r#"
+                [FOO]
+                | a | b |
+                |---|---|
+                | 1 | 2 |
+                "#
+            );
+
+            assert_eq!(Ok(()), ion.get("FOO").unwrap().validate_rows());
+            assert_eq!(Ok(()), Section::new().validate_rows());
+        }
+
+        #[test]
+        fn it_reports_first_ragged_row() {
+            let ion = ion!(
+                r#"
+                [FOO]
+                | a | b |
+                | 1 | 2 |
+                | 3 |
+                | 4 | 5 | 6 |
+                "#
+            );
+
+            assert_eq!(
+                Err(RowShapeError {
+                    row: 2,
+                    len: 1,
+                    expected: 2,
+                }),
+                ion.get("FOO").unwrap().validate_rows()
+            );
+        }
+    }
+
+    mod rows_as_dicts {
+        use super::*;
+
+        fn dict(entries: &[(&str, &str)]) -> Dictionary {
+            entries
+                .iter()
+                .map(|(k, v)| (k.to_string(), Value::new_string(v)))
+                .collect()
+        }
+
+        #[test]
+        fn it_keys_rows_by_header() {
+            let ion = ion!(
+                r#"
+                [FOO]
+                | code | name |
+                |------|------|
+                | RO   | Room |
+                | BB   | Bed  |
+                "#
+            );
+
+            assert_eq!(
+                vec![
+                    dict(&[("code", "RO"), ("name", "Room")]),
+                    dict(&[("code", "BB"), ("name", "Bed")]),
+                ],
+                ion.get("FOO").unwrap().rows_as_dicts()
+            );
+        }
+
+        #[test]
+        fn it_handles_ragged_rows_without_separator() {
+            let ion = ion!(
+                r#"
+                [FOO]
+                | a | b |
+                | 1 |
+                | 2 | 3 | 4 |
+                "#
+            );
+
+            assert_eq!(
+                vec![dict(&[("a", "1")]), dict(&[("a", "2"), ("b", "3")])],
+                ion.get("FOO").unwrap().rows_as_dicts()
+            );
+        }
+
+        #[test]
+        fn it_returns_nothing_for_empty_section() {
+            assert!(Section::new().rows_as_dicts().is_empty());
+        }
+    }
+
     mod with_headers {
         use super::*;
-        use crate::Value;
 
         #[quickcheck]
         fn works_for_any_arbitrary_cell_contents(item: String) -> TestResult {
@@ -0,0 +1,191 @@
+use crate::{Section, Value};
+use std::{error, fmt};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldError {
+    Missing(String),
+    Mismatched {
+        field: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+}
+
+impl error::Error for FieldError {
+    fn description(&self) -> &str {
+        "FieldError"
+    }
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Reads fields of a section's dictionary, collecting every missing or
+/// mismatched field instead of stopping at the first one.
+#[derive(Debug)]
+pub struct SectionReader<'a> {
+    section: &'a Section,
+    errors: Vec<FieldError>,
+}
+
+impl<'a> SectionReader<'a> {
+    pub fn new(section: &'a Section) -> Self {
+        Self {
+            section,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Converts the `key` entry with `convert`, recording a [`FieldError`]
+    /// when the entry is missing or `convert` returns `None`.
+    pub fn field<T>(
+        &mut self,
+        key: &str,
+        expected: &'static str,
+        convert: impl FnOnce(&'a Value) -> Option<T>,
+    ) -> Option<T> {
+        let Some(value) = self.section.get(key) else {
+            self.errors.push(FieldError::Missing(key.to_owned()));
+            return None;
+        };
+
+        let converted = convert(value);
+
+        if converted.is_none() {
+            self.errors.push(FieldError::Mismatched {
+                field: key.to_owned(),
+                expected,
+                found: value.type_str(),
+            });
+        }
+
+        converted
+    }
+
+    /// Like [`SectionReader::field`], but a missing entry is not an error.
+    pub fn optional<T>(
+        &mut self,
+        key: &str,
+        expected: &'static str,
+        convert: impl FnOnce(&'a Value) -> Option<T>,
+    ) -> Option<T> {
+        self.section.get(key)?;
+        self.field(key, expected, convert)
+    }
+
+    pub fn string(&mut self, key: &str) -> Option<String> {
+        self.field(key, "string", |v| v.as_string().cloned())
+    }
+
+    pub fn str(&mut self, key: &str) -> Option<&'a str> {
+        self.field(key, "string", Value::as_str)
+    }
+
+    pub fn i64(&mut self, key: &str) -> Option<i64> {
+        self.field(key, "integer", Value::as_integer)
+    }
+
+    pub fn f64(&mut self, key: &str) -> Option<f64> {
+        self.field(key, "float", Value::as_float)
+    }
+
+    pub fn bool(&mut self, key: &str) -> Option<bool> {
+        self.field(key, "boolean", Value::as_boolean)
+    }
+
+    pub fn array(&mut self, key: &str) -> Option<&'a [Value]> {
+        self.field(key, "array", Value::as_array)
+    }
+
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    pub fn finish(self) -> Result<(), Vec<FieldError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ion, FieldError, Ion, SectionReader};
+
+    #[derive(Debug, PartialEq)]
+    struct Config {
+        name: String,
+        count: i64,
+        enabled: bool,
+        ratio: Option<f64>,
+    }
+
+    #[test]
+    fn reads_all_fields() {
+        let ion = ion!(
+            r#"
+            [CONFIG]
+            name = "foo"
+            count = 3
+            enabled = true
+            "#
+        );
+        let mut reader = SectionReader::new(ion.get("CONFIG").unwrap());
+
+        let config = Config {
+            name: reader.string("name").unwrap_or_default(),
+            count: reader.i64("count").unwrap_or_default(),
+            enabled: reader.bool("enabled").unwrap_or_default(),
+            ratio: reader.optional("ratio", "float", |v| v.as_float()),
+        };
+
+        assert_eq!(Ok(()), reader.finish());
+        assert_eq!(
+            Config {
+                name: "foo".to_owned(),
+                count: 3,
+                enabled: true,
+                ratio: None,
+            },
+            config
+        );
+    }
+
+    #[test]
+    fn collects_every_error() {
+        let ion = ion!(
+            r#"
+            [CONFIG]
+            name = "foo"
+            count = "three"
+            ratio = 1.5
+            "#
+        );
+        let mut reader = ion.get("CONFIG").unwrap().reader();
+
+        assert_eq!(Some("foo".to_owned()), reader.string("name"));
+        assert_eq!(None, reader.i64("count"));
+        assert_eq!(None, reader.bool("enabled"));
+        assert_eq!(
+            Some(1.5),
+            reader.optional("ratio", "float", |v| v.as_float())
+        );
+
+        assert_eq!(
+            Err(vec![
+                FieldError::Mismatched {
+                    field: "count".to_owned(),
+                    expected: "integer",
+                    found: "string",
+                },
+                FieldError::Missing("enabled".to_owned()),
+            ]),
+            reader.finish()
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{Ion, Section, Value};
+use serde::ser::{Serialize, SerializeMap, SerializeStruct, Serializer};
+
+/// Scalars serialize as scalars, arrays as sequences and dictionaries as
+/// maps. `Null` and unresolved `Lazy` values serialize as unit (`null` in
+/// JSON).
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::String(v) => serializer.serialize_str(v),
+            Value::Integer(v) => serializer.serialize_i64(*v),
+            Value::Float(v) => serializer.serialize_f64(*v),
+            Value::Boolean(v) => serializer.serialize_bool(*v),
+            Value::Array(v) => serializer.collect_seq(v),
+            Value::Dictionary(v) => serializer.collect_map(v),
+            Value::Bytes(v) => serializer.serialize_bytes(v),
+            Value::Lazy(_) | Value::Null => serializer.serialize_unit(),
+        }
+    }
+}
+
+/// Serializes as a struct with `dictionary` and `rows` fields.
+impl Serialize for Section {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut section = serializer.serialize_struct("Section", 2)?;
+        section.serialize_field("dictionary", &self.dictionary)?;
+        section.serialize_field("rows", &self.rows)?;
+        section.end()
+    }
+}
+
+/// Serializes as a map from section name to section. Array sections are
+/// sequences of sections in the same map.
+impl Serialize for Ion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let len = self.sections.len() + self.section_arrays.len();
+        let mut map = serializer.serialize_map(Some(len))?;
+
+        for (name, section) in &self.sections {
+            map.serialize_entry(name, section)?;
+        }
+
+        for (name, sections) in &self.section_arrays {
+            map.serialize_entry(name, sections)?;
+        }
+
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ion, Dictionary, Ion, Value};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn value() {
+        let mut dict = Dictionary::new();
+        dict.insert("km".to_owned(), Value::Float(4.1));
+        dict.insert("loc".to_owned(), Value::new_string_array("M"));
+        let value = Value::Array(vec![
+            Value::Integer(1),
+            Value::Boolean(true),
+            Value::Null,
+            Value::Dictionary(dict),
+        ]);
+
+        assert_eq!(
+            r#"[1,true,null,{"km":4.1,"loc":["M"]}]"#,
+            serde_json::to_string(&value).unwrap()
+        );
+    }
+
+    #[test]
+    fn document() {
+        let ion = ion!(
+            r#"
+            [FOO]
+            a = "x"
+            b = 0.5
+            | 1 | y |
+            "#
+        );
+
+        assert_eq!(
+            r#"{"FOO":{"dictionary":{"a":"x","b":0.5},"rows":[["1","y"]]}}"#,
+            serde_json::to_string(&ion).unwrap()
+        );
+
+        let sections: BTreeMap<_, _> = ion.iter().collect();
+        assert_eq!(
+            serde_json::to_string(&ion).unwrap(),
+            serde_json::to_string(&sections).unwrap()
+        );
+    }
+
+    #[test]
+    fn array_sections() {
+        let ion = Ion::from_str_with_arrays("[FOO]\n[[BAR]]\nc = 2\n[[BAR]]\nc = 3\n").unwrap();
+        let json = serde_json::to_value(&ion).unwrap();
+
+        assert_eq!(
+            r#"[{"dictionary":{"c":2},"rows":[]},{"dictionary":{"c":3},"rows":[]}]"#,
+            json["BAR"].to_string()
+        );
+        assert_eq!(r#"{"dictionary":{},"rows":[]}"#, json["FOO"].to_string());
+    }
+}
@@ -0,0 +1,56 @@
+use crate::{Ion, Section, Value};
+use std::fmt::Write;
+
+impl Ion {
+    pub fn to_snapshot(&self) -> String {
+        let mut out = String::new();
+
+        for (name, args) in &self.directives {
+            let _ = writeln!(out, "directive {name}: {args:?}");
+        }
+
+        for (name, section) in &self.sections {
+            let _ = writeln!(out, "section {name:?}");
+            write_section(&mut out, section);
+        }
+
+        for (name, sections) in &self.section_arrays {
+            for (idx, section) in sections.iter().enumerate() {
+                let _ = writeln!(out, "section {name:?} #{idx}");
+                write_section(&mut out, section);
+            }
+        }
+
+        out
+    }
+}
+
+fn write_section(out: &mut String, section: &Section) {
+    for (key, value) in &section.dictionary {
+        let _ = writeln!(out, "  entry {key}: {}", snapshot_value(value));
+    }
+
+    for (idx, row) in section.rows.iter().enumerate() {
+        let cells: Vec<_> = row.iter().map(snapshot_value).collect();
+        let _ = writeln!(out, "  row {idx}: [{}]", cells.join(", "));
+    }
+}
+
+fn snapshot_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => format!("{s:?}"),
+        Value::Array(items) => {
+            let items: Vec<_> = items.iter().map(snapshot_value).collect();
+            format!("array [{}]", items.join(", "))
+        }
+        Value::Dictionary(dict) => {
+            let entries: Vec<_> = dict
+                .iter()
+                .map(|(k, v)| format!("{k}: {}", snapshot_value(v)))
+                .collect();
+            format!("dictionary {{{}}}", entries.join(", "))
+        }
+        Value::Null => "null".to_owned(),
+        _ => format!("{} {value}", value.type_str()),
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{base64, ConversionError, Ion, Section, Value};
+use toml::value::Table;
+
+impl Ion {
+    /// Converts the document to TOML. Every section becomes a table of its
+    /// entries, with its rows stored as an array of arrays under a `rows`
+    /// key. The conversion is lossy: comments, directives and array sections
+    /// are dropped, table headers become ordinary rows and bytes become
+    /// base64 strings.
+    pub fn to_toml_string(&self) -> Result<String, ConversionError> {
+        let mut document = Table::new();
+
+        for (name, section) in self.iter() {
+            document.insert(name.clone(), toml::Value::Table(section_to_toml(section)?));
+        }
+
+        toml::to_string(&document).map_err(|err| ConversionError::InvalidValue(err.to_string()))
+    }
+}
+
+fn section_to_toml(section: &Section) -> Result<Table, ConversionError> {
+    let mut table = Table::new();
+
+    for (key, value) in &section.dictionary {
+        table.insert(key.clone(), value_to_toml(value)?);
+    }
+
+    if !section.rows.is_empty() {
+        if table.contains_key("rows") {
+            return Err(ConversionError::InvalidValue("rows".to_owned()));
+        }
+
+        let rows = section
+            .rows
+            .iter()
+            .map(|row| row.iter().map(value_to_toml).collect::<Result<_, _>>())
+            .map(|row| row.map(toml::Value::Array))
+            .collect::<Result<_, _>>()?;
+
+        table.insert("rows".to_owned(), toml::Value::Array(rows));
+    }
+
+    Ok(table)
+}
+
+fn value_to_toml(value: &Value) -> Result<toml::Value, ConversionError> {
+    Ok(match value {
+        Value::String(v) => toml::Value::String(v.clone()),
+        Value::Integer(v) => toml::Value::Integer(*v),
+        Value::Float(v) => toml::Value::Float(*v),
+        Value::Boolean(v) => toml::Value::Boolean(*v),
+        Value::Array(v) => {
+            toml::Value::Array(v.iter().map(value_to_toml).collect::<Result<_, _>>()?)
+        }
+        Value::Dictionary(v) => toml::Value::Table(
+            v.iter()
+                .map(|(k, v)| value_to_toml(v).map(|v| (k.clone(), v)))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Bytes(v) => toml::Value::String(base64::encode(v)),
+        Value::Lazy(_) | Value::Null => {
+            return Err(ConversionError::InvalidValue(value.to_string()));
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ion, ConversionError, Ion};
+    use std::fs;
+
+    #[test]
+    fn to_toml_string() {
+        let ion: Ion = fs::read_to_string("tests/data/test.ion")
+            .unwrap()
+            .parse()
+            .unwrap();
+
+        let toml: toml::Value = toml::from_str(&ion.to_toml_string().unwrap()).unwrap();
+
+        assert_eq!(Some("another"), toml["dict"]["second"].as_str());
+        assert_eq!(Some(true), toml["dict"]["bool"].as_bool());
+        assert_eq!(Some(3), toml["dict"]["ary"][2].as_integer());
+        assert_eq!(Some("two"), toml["table"]["rows"][2][1].as_str());
+        assert_eq!(5, toml["table"]["rows"].as_array().unwrap().len());
+        assert_eq!(Some(2), toml["mixed"]["B"].as_integer());
+        assert_eq!(Some("that"), toml["mixed"]["rows"][1][0].as_str());
+    }
+
+    #[test]
+    fn to_toml_string_with_rows_entry() {
+        let ion = ion!(
+            r#"
+            [FOO]
+            rows = 1
+            | a |
+            "#
+        );
+
+        assert_eq!(
+            Err(ConversionError::InvalidValue("rows".to_owned())),
+            ion.to_toml_string()
+        );
+    }
+}
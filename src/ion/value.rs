@@ -1,4 +1,7 @@
+use crate::parser::{self, ParserError};
 use crate::{Dictionary, FromIon, IonError, Row};
+use std::cmp::Ordering;
+use std::ops::Range;
 use std::str::FromStr;
 
 #[derive(Debug, PartialEq, Clone)]
@@ -9,6 +12,9 @@ pub enum Value {
     Boolean(bool),
     Array(Row),
     Dictionary(Dictionary),
+    Bytes(Vec<u8>),
+    Lazy(Range<usize>),
+    Null,
 }
 
 impl Value {
@@ -32,6 +38,9 @@ impl Value {
             Value::Boolean(..) => "boolean",
             Value::Array(..) => "array",
             Value::Dictionary(..) => "dictionary",
+            Value::Bytes(..) => "bytes",
+            Value::Lazy(..) => "lazy",
+            Value::Null => "null",
         }
     }
 
@@ -53,6 +62,15 @@ impl Value {
         }
     }
 
+    /// Returns the content of a string, or the `Display` form of any other
+    /// value.
+    pub fn to_display_string(&self) -> String {
+        match self {
+            Value::String(v) => v.clone(),
+            _ => self.to_string(),
+        }
+    }
+
     pub fn as_integer(&self) -> Option<i64> {
         match self {
             Value::Integer(v) => Some(*v),
@@ -60,13 +78,23 @@ impl Value {
         }
     }
 
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Value::Integer(_))
+    }
+
+    /// Returns a float, widening an integer to `f64`.
     pub fn as_float(&self) -> Option<f64> {
         match self {
             Value::Float(v) => Some(*v),
+            Value::Integer(v) => Some(*v as f64),
             _ => None,
         }
     }
 
+    pub fn is_float(&self) -> bool {
+        matches!(self, Value::Float(_))
+    }
+
     pub fn as_boolean(&self) -> Option<bool> {
         match self {
             Value::Boolean(v) => Some(*v),
@@ -74,13 +102,25 @@ impl Value {
         }
     }
 
-    pub fn as_array(&self) -> Option<&Vec<Value>> {
+    pub fn as_bool(&self) -> Option<bool> {
+        self.as_boolean()
+    }
+
+    pub fn is_boolean(&self) -> bool {
+        matches!(self, Value::Boolean(_))
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
         match self {
             Value::Array(v) => Some(v),
             _ => None,
         }
     }
 
+    pub fn is_array(&self) -> bool {
+        matches!(self, Value::Array(_))
+    }
+
     pub fn as_dictionary(&self) -> Option<&Dictionary> {
         match self {
             Value::Dictionary(v) => Some(v),
@@ -88,13 +128,139 @@ impl Value {
         }
     }
 
-    pub fn get(&self, name: &str) -> Option<&Value> {
+    pub fn is_dictionary(&self) -> bool {
+        matches!(self, Value::Dictionary(_))
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
         match self {
-            Value::Dictionary(v) => v.get(name),
+            Value::Bytes(v) => Some(v),
             _ => None,
         }
     }
 
+    pub fn is_bytes(&self) -> bool {
+        matches!(self, Value::Bytes(_))
+    }
+
+    /// Parses a [`Value::Lazy`] from the `input` it was read from. Other
+    /// values are returned as they are.
+    pub fn resolve(&self, input: &str) -> Result<Value, ParserError> {
+        match self {
+            Value::Lazy(span) => parser::parse_value_at(input, span.clone()),
+            _ => Ok(self.clone()),
+        }
+    }
+
+    /// Looks up a dot-separated `path`, where segments index into
+    /// dictionaries by key and into arrays by position, e.g. `"dist.beach_km"`
+    /// or `"loc.0"`. A dictionary key containing dots is found as a whole.
+    pub fn get(&self, path: &str) -> Option<&Value> {
+        if let Some(value) = self.as_dictionary().and_then(|v| v.get(path)) {
+            return Some(value);
+        }
+
+        path.split('.')
+            .try_fold(self, |value, segment| match value {
+                Value::Dictionary(v) => v.get(segment),
+                Value::Array(v) => v.get(segment.parse::<usize>().ok()?),
+                _ => None,
+            })
+    }
+
+    /// Concatenates the elements of an array of arrays, one level deep.
+    /// Returns `None` for non-arrays and arrays with non-array elements.
+    pub fn flatten_array(&self) -> Option<Value> {
+        let mut flattened = Vec::new();
+
+        for item in self.as_array()? {
+            flattened.extend_from_slice(item.as_array()?);
+        }
+
+        Some(Value::Array(flattened))
+    }
+
+    /// Sorts the elements of an array in place, leaving other variants untouched.
+    ///
+    /// The sort is stable. Values of different variants are ordered by their
+    /// declaration order in `Value` (so every string sorts before every integer),
+    /// and floats by [`f64::total_cmp`], so `NaN` sorts after all other floats.
+    pub fn sort(&mut self) {
+        if let Value::Array(items) = self {
+            items.sort_by(Value::total_cmp);
+        }
+    }
+
+    /// Like [`Value::sort`], but first sorts every array nested inside arrays and
+    /// dictionary values.
+    pub fn sort_recursive(&mut self) {
+        match self {
+            Value::Array(items) => items.iter_mut().for_each(Value::sort_recursive),
+            Value::Dictionary(dict) => dict.values_mut().for_each(Value::sort_recursive),
+            _ => (),
+        }
+
+        self.sort();
+    }
+
+    /// Removes the entries of a dictionary for which `f` returns `false`,
+    /// leaving other variants untouched. Combine with [`Value::visit_mut`]
+    /// to prune nested dictionaries too.
+    pub fn retain(&mut self, f: impl Fn(&str, &Value) -> bool) {
+        if let Value::Dictionary(dict) = self {
+            dict.retain(|k, v| f(k, v));
+        }
+    }
+
+    /// Calls `f` on this value and then on every value nested in it, visiting
+    /// a container before its elements.
+    pub fn visit_mut(&mut self, f: &mut impl FnMut(&mut Value)) {
+        f(self);
+
+        match self {
+            Value::Array(items) => items.iter_mut().for_each(|v| v.visit_mut(f)),
+            Value::Dictionary(dict) => dict.values_mut().for_each(|v| v.visit_mut(f)),
+            _ => (),
+        }
+    }
+
+    /// Compares values like `==`, except that floats (also nested in arrays
+    /// and dictionaries) are equal when they differ by at most `epsilon`.
+    pub fn approx_eq(&self, other: &Value, epsilon: f64) -> bool {
+        match (self, other) {
+            (Value::Float(a), Value::Float(b)) => a == b || (a - b).abs() <= epsilon,
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, epsilon))
+            }
+            (Value::Dictionary(a), Value::Dictionary(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b)
+                        .all(|((ka, a), (kb, b))| ka == kb && a.approx_eq(b, epsilon))
+            }
+            _ => self == other,
+        }
+    }
+
+    /// Turns an integral float into the equal `Value::Integer`, e.g. `2.0`
+    /// into `2`. Floats with a fraction, NaN, infinities and integral floats
+    /// outside `i64` (below `-2^63` or from `2^63` on) are returned
+    /// unchanged, as is any other value.
+    pub fn canonical_number(&self) -> Value {
+        match self {
+            Value::Float(v)
+                if v.fract() == 0.0 && *v >= i64::MIN as f64 && *v < i64::MAX as f64 =>
+            {
+                Value::Integer(*v as i64)
+            }
+            _ => self.clone(),
+        }
+    }
+
     pub fn from_ion<F>(&self) -> Result<F, F::Err>
     where
         F: FromIon<Value>,
@@ -113,6 +279,63 @@ impl Value {
     }
 }
 
+impl Value {
+    fn rank(&self) -> u8 {
+        match self {
+            Value::String(..) => 0,
+            Value::Integer(..) => 1,
+            Value::Float(..) => 2,
+            Value::Boolean(..) => 3,
+            Value::Array(..) => 4,
+            Value::Dictionary(..) => 5,
+            Value::Bytes(..) => 6,
+            Value::Lazy(..) => 7,
+            Value::Null => 8,
+        }
+    }
+}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::String(a), Value::String(b)) => a.partial_cmp(b),
+            (Value::Integer(a), Value::Integer(b)) => a.partial_cmp(b),
+            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+            (Value::Boolean(a), Value::Boolean(b)) => a.partial_cmp(b),
+            (Value::Array(a), Value::Array(b)) => a.partial_cmp(b),
+            (Value::Dictionary(a), Value::Dictionary(b)) => a.partial_cmp(b),
+            (Value::Bytes(a), Value::Bytes(b)) => a.partial_cmp(b),
+            (Value::Lazy(a), Value::Lazy(b)) => (a.start, a.end).partial_cmp(&(b.start, b.end)),
+            _ => self.rank().partial_cmp(&other.rank()),
+        }
+    }
+}
+
+impl Value {
+    /// Like [`PartialOrd`], but a total order: floats are compared with
+    /// [`f64::total_cmp`], also inside arrays and dictionaries.
+    pub(crate) fn total_cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Float(a), Value::Float(b)) => a.total_cmp(b),
+            (Value::Array(a), Value::Array(b)) => a
+                .iter()
+                .zip(b)
+                .map(|(a, b)| a.total_cmp(b))
+                .find(|ord| ord.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            (Value::Dictionary(a), Value::Dictionary(b)) => a
+                .iter()
+                .zip(b)
+                .map(|((ka, va), (kb, vb))| ka.cmp(kb).then_with(|| va.total_cmp(vb)))
+                .find(|ord| ord.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            _ => self
+                .partial_cmp(other)
+                .expect("only floats are partially ordered"),
+        }
+    }
+}
+
 impl FromStr for Value {
     type Err = IonError;
 
@@ -123,7 +346,7 @@ impl FromStr for Value {
 
 #[cfg(test)]
 mod tests {
-    use crate::Value;
+    use crate::{Dictionary, Value};
 
     #[test]
     fn integer() {
@@ -131,6 +354,213 @@ mod tests {
         assert_eq!(1, v.parse().unwrap());
     }
 
+    #[test]
+    fn sort_mixed_scalars() {
+        let mut v = Value::Array(vec![
+            Value::Integer(3),
+            Value::new_string("b"),
+            Value::Boolean(true),
+            Value::Integer(1),
+            Value::Float(0.5),
+            Value::new_string("a"),
+            Value::Boolean(false),
+        ]);
+        v.sort();
+
+        assert_eq!(
+            Value::Array(vec![
+                Value::new_string("a"),
+                Value::new_string("b"),
+                Value::Integer(1),
+                Value::Integer(3),
+                Value::Float(0.5),
+                Value::Boolean(false),
+                Value::Boolean(true),
+            ]),
+            v
+        );
+    }
+
+    #[test]
+    fn sort_nested() {
+        let inner = || Value::Array(vec![Value::Integer(2), Value::Integer(1)]);
+        let mut dict = Dictionary::new();
+        dict.insert("k".to_owned(), inner());
+        let original = Value::Array(vec![inner(), Value::Dictionary(dict), Value::Integer(0)]);
+
+        let mut shallow = original.clone();
+        shallow.sort();
+        assert_eq!(Some(&inner()), shallow.as_array().unwrap().get(1));
+
+        let mut deep = original;
+        deep.sort_recursive();
+        let sorted = Value::Array(vec![Value::Integer(1), Value::Integer(2)]);
+        let items = deep.as_array().unwrap();
+        assert_eq!(Value::Integer(0), items[0]);
+        assert_eq!(sorted, items[1]);
+        assert_eq!(Some(&sorted), items[2].get("k"));
+    }
+
+    #[test]
+    fn sort_nan() {
+        let mut v = Value::Array(vec![
+            Value::Float(3.0),
+            Value::Float(f64::NAN),
+            Value::Float(1.0),
+            Value::Array(vec![Value::Float(f64::NAN)]),
+            Value::Array(vec![Value::Float(2.0)]),
+        ]);
+        v.sort();
+
+        let items = v.as_array().unwrap();
+        assert_eq!(Some(1.0), items[0].as_float());
+        assert_eq!(Some(3.0), items[1].as_float());
+        assert!(items[2].as_float().unwrap().is_nan());
+        assert_eq!(Value::Array(vec![Value::Float(2.0)]), items[3]);
+        assert!(items[4].as_array().unwrap()[0].as_float().unwrap().is_nan());
+    }
+
+    #[test]
+    fn sort_ignores_non_arrays() {
+        let mut v = Value::new_string("b a");
+        v.sort();
+        assert_eq!(Value::new_string("b a"), v);
+    }
+
+    #[test]
+    fn retain() {
+        let mut inner = Dictionary::new();
+        inner.insert("_private".to_owned(), Value::Integer(1));
+        inner.insert("public".to_owned(), Value::Integer(2));
+        let mut dict = Dictionary::new();
+        dict.insert("_skip".to_owned(), Value::Boolean(true));
+        dict.insert("inner".to_owned(), Value::Dictionary(inner));
+        let mut v = Value::Dictionary(dict);
+
+        v.retain(|k, _| !k.starts_with('_'));
+        assert_eq!("{ inner = { _private = 1, public = 2 } }", v.to_string());
+
+        v.visit_mut(&mut |v| v.retain(|k, _| !k.starts_with('_')));
+        assert_eq!("{ inner = { public = 2 } }", v.to_string());
+
+        v.retain(|_, v| v.as_integer().is_some());
+        assert_eq!("{  }", v.to_string());
+
+        let mut array = Value::new_string_array("a");
+        array.retain(|_, _| false);
+        assert_eq!(Value::new_string_array("a"), array);
+    }
+
+    #[test]
+    fn visit_mut() {
+        let mut dict = Dictionary::new();
+        dict.insert("a".to_owned(), Value::new_string("  x "));
+        dict.insert(
+            "b".to_owned(),
+            Value::Array(vec![Value::new_string(" y"), Value::Integer(1)]),
+        );
+        let mut v = Value::Array(vec![Value::Dictionary(dict), Value::new_string("z  ")]);
+
+        let mut visited = 0;
+        v.visit_mut(&mut |v| {
+            visited += 1;
+            if let Value::String(s) = v {
+                *s = s.trim().to_owned();
+            }
+        });
+
+        assert_eq!(7, visited);
+        assert_eq!("[ { a = \"x\", b = [ \"y\", 1 ] }, \"z\" ]", v.to_string());
+    }
+
+    #[test]
+    fn to_display_string() {
+        let mut dict = Dictionary::new();
+        dict.insert("a".to_owned(), Value::new_string("x"));
+
+        assert_eq!("a \"b\"", Value::new_string("a \"b\"").to_display_string());
+        assert_eq!("-1", Value::Integer(-1).to_display_string());
+        assert_eq!("4.5", Value::Float(4.5).to_display_string());
+        assert_eq!("true", Value::Boolean(true).to_display_string());
+        assert_eq!(
+            "[ \"x\", 1 ]",
+            Value::Array(vec![Value::new_string("x"), Value::Integer(1)]).to_display_string()
+        );
+        assert_eq!("{ a = \"x\" }", Value::Dictionary(dict).to_display_string());
+        assert_eq!("b64\"AQI=\"", Value::Bytes(vec![1, 2]).to_display_string());
+    }
+
+    #[test]
+    fn flatten_array() {
+        let nested = Value::Array(vec![
+            Value::Array(vec![Value::Integer(1), Value::Integer(2)]),
+            Value::Array(vec![]),
+            Value::Array(vec![Value::Integer(3), Value::new_string_array("4")]),
+        ]);
+        assert_eq!(
+            "[ 1, 2, 3, [ \"4\" ] ]",
+            nested.flatten_array().unwrap().to_string()
+        );
+
+        let flat = Value::Array(vec![Value::Integer(1), Value::Integer(2)]);
+        assert_eq!(None, flat.flatten_array());
+
+        let mixed = Value::Array(vec![Value::new_array(Value::Integer(1)), Value::Integer(2)]);
+        assert_eq!(None, mixed.flatten_array());
+
+        assert_eq!(
+            Some(Value::Array(vec![])),
+            Value::Array(vec![]).flatten_array()
+        );
+        assert_eq!(None, Value::Integer(1).flatten_array());
+    }
+
+    #[test]
+    fn canonical_number() {
+        assert_eq!(Value::Integer(2), Value::Float(2.0).canonical_number());
+        assert_eq!(Value::Integer(-3), Value::Float(-3.0).canonical_number());
+        assert_eq!(Value::Float(2.5), Value::Float(2.5).canonical_number());
+        assert_eq!(Value::Float(1e19), Value::Float(1e19).canonical_number());
+        assert_eq!(
+            Value::Float(i64::MAX as f64),
+            Value::Float(i64::MAX as f64).canonical_number()
+        );
+        assert_eq!(
+            Value::Integer(i64::MIN),
+            Value::Float(i64::MIN as f64).canonical_number()
+        );
+        assert_eq!(
+            Value::Float(f64::INFINITY),
+            Value::Float(f64::INFINITY).canonical_number()
+        );
+        assert!(matches!(Value::Float(f64::NAN).canonical_number(), Value::Float(v) if v.is_nan()));
+        assert_eq!(Value::Integer(7), Value::Integer(7).canonical_number());
+        assert_eq!(
+            Value::String("2.0".to_owned()),
+            Value::String("2.0".to_owned()).canonical_number()
+        );
+    }
+
+    #[test]
+    fn approx_eq() {
+        let a = Value::Float(4.1);
+        let b = Value::Float(4.1000001);
+        assert!(a.approx_eq(&b, 1e-6));
+        assert!(!a.approx_eq(&b, 1e-8));
+        assert!(Value::Float(f64::INFINITY).approx_eq(&Value::Float(f64::INFINITY), 1e-6));
+
+        let mut dict = Dictionary::new();
+        dict.insert("a".to_owned(), Value::Array(vec![a, Value::Integer(1)]));
+        let mut other = Dictionary::new();
+        other.insert("a".to_owned(), Value::Array(vec![b, Value::Integer(1)]));
+        assert!(Value::Dictionary(dict.clone()).approx_eq(&Value::Dictionary(other.clone()), 1e-6));
+
+        other.insert("b".to_owned(), Value::Integer(1));
+        assert!(!Value::Dictionary(dict).approx_eq(&Value::Dictionary(other), 1e-6));
+        assert!(!Value::Integer(1).approx_eq(&Value::Float(1.0), 1e-6));
+        assert!(Value::new_string("x").approx_eq(&Value::new_string("x"), 0.0));
+    }
+
     #[test]
     fn float() {
         let v: Value = "4.0".parse().unwrap();
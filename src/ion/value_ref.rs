@@ -0,0 +1,64 @@
+use crate::{Row, Section, Value};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+/// A value read by [`Parser::read_borrowed`]. Strings and cells without
+/// escapes borrow from the input, everything else, including strings nested
+/// in arrays and dictionaries, is owned.
+///
+/// [`Parser::read_borrowed`]: crate::Parser::read_borrowed
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValueRef<'a> {
+    String(Cow<'a, str>),
+    Owned(Value),
+}
+
+impl<'a> ValueRef<'a> {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ValueRef::String(s) => Some(s),
+            ValueRef::Owned(v) => v.as_str(),
+        }
+    }
+
+    pub fn into_owned(self) -> Value {
+        match self {
+            ValueRef::String(s) => Value::String(s.into_owned()),
+            ValueRef::Owned(v) => v,
+        }
+    }
+}
+
+impl<'a> From<ValueRef<'a>> for Value {
+    fn from(value: ValueRef<'a>) -> Self {
+        value.into_owned()
+    }
+}
+
+/// A [`Section`] of [`ValueRef`]s with keys borrowed from the input.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SectionRef<'a> {
+    pub dictionary: BTreeMap<&'a str, ValueRef<'a>>,
+    pub rows: Vec<Vec<ValueRef<'a>>>,
+}
+
+impl<'a> SectionRef<'a> {
+    pub fn get(&self, key: &str) -> Option<&ValueRef<'a>> {
+        self.dictionary.get(key)
+    }
+
+    pub fn into_owned(self) -> Section {
+        Section {
+            dictionary: self
+                .dictionary
+                .into_iter()
+                .map(|(key, value)| (key.to_owned(), value.into_owned()))
+                .collect(),
+            rows: self
+                .rows
+                .into_iter()
+                .map(|row| row.into_iter().map(ValueRef::into_owned).collect::<Row>())
+                .collect(),
+        }
+    }
+}
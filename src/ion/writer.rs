@@ -0,0 +1,479 @@
+use super::display::Floats;
+use super::pretty::{self, Style};
+use crate::{Ion, Layout, Row, Section, SectionLayout, Value};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+/// Writes `sections` as Ion text that [`Parser::read`](crate::Parser::read)
+/// reads back into an equal map. [`Value::Lazy`] values are not resolved and
+/// have to be resolved beforehand.
+pub fn to_ion_string(sections: &BTreeMap<String, Section>) -> String {
+    Writer::new().write_sections(sections)
+}
+
+/// Serializes documents to Ion text. With the default options the output is
+/// the same as the `Display` implementations, except that integral floats
+/// keep their `.0`, e.g. `2.0`, so that they read back as floats.
+#[derive(Clone, Debug)]
+pub struct Writer {
+    sort_rows_by: Option<usize>,
+    minimal_spacing: bool,
+    layout: Option<Layout>,
+    array_padding: bool,
+    align_equals: bool,
+    dictionary_multiline: bool,
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Self {
+            sort_rows_by: None,
+            minimal_spacing: false,
+            layout: None,
+            array_padding: true,
+            align_equals: false,
+            dictionary_multiline: false,
+        }
+    }
+}
+
+impl Writer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Writes a space inside array brackets, `[ 1, "foo" ]`, which is the
+    /// default. Without it arrays are written as `[1, "foo"]`.
+    pub fn with_array_padding(mut self, array_padding: bool) -> Self {
+        self.array_padding = array_padding;
+        self
+    }
+
+    /// Pads the keys of every section so that their `=` line up. A section
+    /// layout with an `equals_column` takes precedence.
+    pub fn with_align_equals(mut self, align_equals: bool) -> Self {
+        self.align_equals = align_equals;
+        self
+    }
+
+    /// Writes every non-empty dictionary with one entry per line, indented
+    /// by four spaces per level, like [`Value::to_pretty_string`] does.
+    pub fn with_dictionary_multiline(mut self, dictionary_multiline: bool) -> Self {
+        self.dictionary_multiline = dictionary_multiline;
+        self
+    }
+
+    /// Emits the data rows of every section stably sorted by the given
+    /// column. The header and `---` separator rows stay on top and rows too
+    /// short to have the column sort first.
+    pub fn with_sort_rows_by(mut self, sort_rows_by: Option<usize>) -> Self {
+        self.sort_rows_by = sort_rows_by;
+        self
+    }
+
+    /// Omits all optional whitespace, e.g. `key=[1,"a"]` and `|a|b|`.
+    pub fn with_minimal_spacing(mut self, minimal_spacing: bool) -> Self {
+        self.minimal_spacing = minimal_spacing;
+        self
+    }
+
+    /// Reproduces the blank lines between sections and the `=` alignment
+    /// recorded by [`Parser::read_with_layout`](crate::Parser::read_with_layout).
+    /// Sections missing from the layout are written as usual.
+    pub fn with_layout(mut self, layout: Layout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    pub fn write_ion(&self, ion: &Ion) -> String {
+        let sections = ion
+            .sections
+            .iter()
+            .map(|(name, section)| (name, section, false));
+        let section_arrays = ion
+            .section_arrays
+            .iter()
+            .flat_map(|(name, sections)| sections.iter().map(move |section| (name, section, true)));
+
+        self.write_all(sections.chain(section_arrays))
+    }
+
+    /// Like [`Writer::write_ion`], for a map as returned by
+    /// [`Parser::read`](crate::Parser::read).
+    pub fn write_sections(&self, sections: &BTreeMap<String, Section>) -> String {
+        self.write_all(
+            sections
+                .iter()
+                .map(|(name, section)| (name, section, false)),
+        )
+    }
+
+    fn write_all<'a>(
+        &self,
+        sections: impl Iterator<Item = (&'a String, &'a Section, bool)>,
+    ) -> String {
+        let mut out = String::new();
+
+        for (idx, (name, section, is_array)) in sections.enumerate() {
+            let layout = self.section_layout(name);
+            let blank_lines = layout.map_or(usize::from(idx > 0), |l| l.blank_lines_before);
+
+            out.push_str(&"\n".repeat(blank_lines));
+            self.write_section_with_header(&mut out, name, section, is_array);
+        }
+
+        let trailing = self.layout.as_ref().map_or(1, |l| l.trailing_blank_lines);
+        out.push_str(&"\n".repeat(trailing));
+        out
+    }
+
+    pub fn write_section(&self, section: &Section) -> String {
+        let mut out = String::new();
+        self.write_section_into(&mut out, section, None);
+        out
+    }
+
+    fn section_layout(&self, name: &str) -> Option<&SectionLayout> {
+        self.layout.as_ref()?.sections.get(name)
+    }
+
+    fn write_section_with_header(
+        &self,
+        out: &mut String,
+        name: &str,
+        section: &Section,
+        is_array: bool,
+    ) {
+        let (open, close) = if is_array { ("[[", "]]") } else { ("[", "]") };
+
+        out.push_str(open);
+        out.push_str(name);
+        out.push_str(close);
+        out.push('\n');
+        self.write_section_into(out, section, self.section_layout(name));
+    }
+
+    fn write_section_into(
+        &self,
+        out: &mut String,
+        section: &Section,
+        layout: Option<&SectionLayout>,
+    ) {
+        let equals_column = layout.and_then(|l| l.equals_column).or_else(|| {
+            let longest_key = section.dictionary.keys().map(String::len).max();
+            longest_key.filter(|_| self.align_equals).map(|len| len + 1)
+        });
+
+        for (key, value) in &section.dictionary {
+            out.push_str(key);
+
+            if self.minimal_spacing {
+                out.push('=');
+                value.write_minimal(out, Floats::RoundTrip);
+            } else {
+                let padding = equals_column.map_or(1, |col| col.saturating_sub(key.len()).max(1));
+                out.push_str(&" ".repeat(padding));
+                out.push_str("= ");
+                self.write_value(out, value);
+            }
+
+            out.push('\n');
+        }
+
+        for row in self.rows(section) {
+            for cell in row {
+                if self.minimal_spacing {
+                    out.push('|');
+                    cell.write_cell(out, Floats::RoundTrip);
+                } else {
+                    out.push_str("| ");
+                    cell.write_cell(out, Floats::RoundTrip);
+                    out.push(' ');
+                }
+            }
+
+            out.push_str("|\n");
+        }
+    }
+
+    fn write_value(&self, out: &mut String, value: &Value) {
+        let style = Style {
+            indent: 4,
+            multiline_arrays: false,
+            multiline_dictionaries: self.dictionary_multiline,
+            trailing_comma: false,
+            array_padding: self.array_padding,
+            pad_empty: true,
+            floats: Floats::RoundTrip,
+        };
+
+        pretty::write_value(out, value, &style, 0);
+    }
+
+    fn rows<'a>(&self, section: &'a Section) -> Vec<&'a Row> {
+        let mut rows: Vec<_> = section.rows.iter().collect();
+
+        if let Some(col) = self.sort_rows_by {
+            let header_len = section.rows.len() - section.rows_without_header().len();
+            rows[header_len..].sort_by(|a, b| compare_cells(a.get(col), b.get(col)));
+        }
+
+        rows
+    }
+}
+
+fn compare_cells(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.total_cmp(b),
+        (a, b) => a.is_some().cmp(&b.is_some()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ion, to_ion_string, Ion, Parser, Section, Value, Writer};
+    use std::fs;
+
+    #[test]
+    fn to_ion_string_round_trips() {
+        let docs = [
+            fs::read_to_string("tests/data/test.ion").unwrap(),
+            fs::read_to_string("tests/data/hotel.ion").unwrap(),
+            "a = 1\nb = \"x\\\"y\\\\z\\n\\t\"\nc = [ 1, [ 2.0 ], { d = 1e19 } ]\n".to_owned(),
+            r#"
+                [FOO]
+                | a\|b | c\\d | "e" | f\ng |
+                |-----|------|-----|-------|
+                |     | 1.5  | x   |
+                [BAR]
+                empty = { }
+            "#
+            .to_owned(),
+        ];
+
+        for raw in docs {
+            let sections = Parser::new(&raw).read().unwrap();
+            let written = to_ion_string(&sections);
+
+            assert_eq!(Some(sections), Parser::new(&written).read(), "{written}");
+        }
+    }
+
+    #[test]
+    fn lazy_values_are_not_resolved() {
+        let raw = "a = [1, 2]\n";
+        let mut sections = Parser::new(raw).with_lazy_values(true).read().unwrap();
+
+        let written = to_ion_string(&sections);
+        assert!(written.contains("a = lazy(4..10)\n"));
+        assert_eq!(None, Parser::new(&written).read());
+
+        let root = sections.get_mut("root").unwrap();
+        for value in root.dictionary.values_mut() {
+            *value = value.resolve(raw).unwrap();
+        }
+        assert_eq!(Parser::new(raw).read().unwrap(), sections);
+    }
+
+    fn options_ion() -> Ion {
+        ion!(
+            r#"
+            [A]
+            a = [ 1, "foo", [ ] ]
+            long_key = { x = 1, y = { z = [ 2 ] } }
+            "#
+        )
+    }
+
+    #[test]
+    fn array_padding() {
+        let ion = options_ion();
+
+        assert_eq!(
+            "[A]\na = [1, \"foo\", []]\nlong_key = { x = 1, y = { z = [2] } }\n\n",
+            Writer::new().with_array_padding(false).write_ion(&ion)
+        );
+    }
+
+    #[test]
+    fn align_equals() {
+        let ion = options_ion();
+
+        assert_eq!(
+            "[A]\na        = [ 1, \"foo\", [  ] ]\nlong_key = { x = 1, y = { z = [ 2 ] } }\n\n",
+            Writer::new().with_align_equals(true).write_ion(&ion)
+        );
+    }
+
+    #[test]
+    fn dictionary_multiline() {
+        let ion = options_ion();
+        let written = Writer::new()
+            .with_dictionary_multiline(true)
+            .write_ion(&ion);
+
+        assert_eq!(
+            "[A]\na = [ 1, \"foo\", [  ] ]\nlong_key = {\n    x = 1,\n    y = {\n        z = [ 2 ]\n    }\n}\n\n",
+            written
+        );
+        assert_eq!(ion.get("A"), ion!(written).get("A"));
+    }
+
+    #[test]
+    fn write_cells() {
+        let raw = "[FOO]\n| a\\|b | \"c\" |\n";
+        let sections = Parser::new(raw).read().unwrap();
+
+        assert_eq!(
+            vec![Value::new_string("a|b"), Value::new_string("\"c\"")],
+            sections["FOO"].rows[0]
+        );
+        assert_eq!("[FOO]\n| a\\|b | \"c\" |\n\n", to_ion_string(&sections));
+    }
+
+    #[test]
+    fn default_matches_display() {
+        let ion = ion!(fs::read_to_string("tests/data/test.ion").unwrap());
+        assert_eq!(ion.to_string(), Writer::new().write_ion(&ion));
+
+        let ion = Ion::from_str_with_arrays("[[item]]\na = 1\n[[item]]\na = 2\n").unwrap();
+        assert_eq!(ion.to_string(), Writer::new().write_ion(&ion));
+    }
+
+    #[test]
+    fn minimal_spacing() {
+        let raw = r#"
+            [A]
+            ary = [ "a", [ 1, 2 ], { x = "y z" } ]
+            dict = { a = 1, b = { c = [ ] } }
+            s = "q\"uote"
+            | code | name  |
+            |------|-------|
+            | RO   | Room  |
+            [B]
+            b = true
+        "#;
+        let ion = ion!(raw);
+
+        let minimal = Writer::new().with_minimal_spacing(true).write_ion(&ion);
+        let expected = r#"[A]
+ary=["a",[1,2],{x="y z"}]
+dict={a=1,b={c=[]}}
+s="q\"uote"
+|code|name|
+|------|-------|
+|RO|Room|
+
+[B]
+b=true
+
+"#;
+        assert_eq!(expected, minimal);
+
+        let reparsed = ion!(minimal);
+        for (name, section) in ion.iter() {
+            assert_eq!(Some(section), reparsed.get(name));
+        }
+
+        assert_eq!(ion.to_string(), Writer::new().write_ion(&ion));
+    }
+
+    #[test]
+    fn layout() {
+        let raw = "\n[A]\na   = 1\nbb  = [ 2 ]\n\n\n\n[B]\nc = \"x\"\n[C]\n| 1 | 2 |\n\n";
+        let (map, layout) = Parser::new(raw).read_with_layout().unwrap();
+
+        assert_eq!(1, layout.sections["A"].blank_lines_before);
+        assert_eq!(Some(4), layout.sections["A"].equals_column);
+        assert_eq!(3, layout.sections["B"].blank_lines_before);
+        assert_eq!(0, layout.sections["C"].blank_lines_before);
+        assert_eq!(None, layout.sections["C"].equals_column);
+        assert_eq!(1, layout.trailing_blank_lines);
+
+        let ion = Ion::new(map);
+        assert_eq!(raw, Writer::new().with_layout(layout).write_ion(&ion));
+        assert_eq!(ion.to_string(), Writer::new().write_ion(&ion));
+    }
+
+    #[test]
+    fn layout_with_unaligned_entries() {
+        let raw = "[A]\na = 1\nbb  = 2\n";
+        let (map, layout) = Parser::new(raw).read_with_layout().unwrap();
+
+        assert_eq!(None, layout.sections["A"].equals_column);
+        assert_eq!(0, layout.trailing_blank_lines);
+        assert_eq!(
+            "[A]\na = 1\nbb = 2\n",
+            Writer::new().with_layout(layout).write_ion(&Ion::new(map))
+        );
+    }
+
+    #[test]
+    fn sort_rows_by() {
+        let ion = ion!(
+            r#"
+            [FOO]
+            id = 1
+            | code | n |
+            |------|---|
+            | SGL  | 2 |
+            | DBL  | 1 |
+            | TPL  | 2 |
+            | APT  |
+            | DBL  | 0 |
+            "#
+        );
+        let section = ion.get("FOO").unwrap();
+
+        let expected = "\
+id = 1
+| code | n |
+| ------ | --- |
+| APT |
+| DBL | 1 |
+| DBL | 0 |
+| SGL | 2 |
+| TPL | 2 |
+";
+        assert_eq!(
+            expected,
+            Writer::new()
+                .with_sort_rows_by(Some(0))
+                .write_section(section)
+        );
+
+        let expected = "\
+id = 1
+| code | n |
+| ------ | --- |
+| APT |
+| DBL | 0 |
+| DBL | 1 |
+| SGL | 2 |
+| TPL | 2 |
+";
+        assert_eq!(
+            expected,
+            Writer::new()
+                .with_sort_rows_by(Some(1))
+                .write_section(section)
+        );
+
+        let mut floats = Section::new();
+        for n in [3.0, f64::NAN, 1.0, 2.0] {
+            floats.rows.push(vec![Value::Float(n)]);
+        }
+
+        assert_eq!(
+            "| 1.0 |\n| 2.0 |\n| 3.0 |\n| nan |\n",
+            Writer::new()
+                .with_sort_rows_by(Some(0))
+                .write_section(&floats)
+        );
+
+        assert_eq!(
+            section.to_string(),
+            Writer::new().with_sort_rows_by(None).write_section(section)
+        );
+    }
+}
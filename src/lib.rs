@@ -1,10 +1,15 @@
+mod base64;
 #[macro_use]
 mod ion;
+mod line_ending;
 mod parser;
 
 pub use self::ion::*;
+pub use self::line_ending::*;
 pub use self::parser::*;
 use std::collections::BTreeMap;
 
 pub type Dictionary = BTreeMap<String, Value>;
 pub type Row = Vec<Value>;
+pub type Comments = Vec<(usize, String)>;
+pub type AttachedComments = Vec<(CommentTarget, Vec<String>)>;
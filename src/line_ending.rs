@@ -0,0 +1,67 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+    Cr,
+    Mixed,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf | LineEnding::Mixed => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+/// Detects the line ending style used by `s`. A text without any line break
+/// is reported as [`LineEnding::Lf`].
+pub fn detect_line_ending(s: &str) -> LineEnding {
+    let mut detected = None;
+    let mut bytes = s.bytes().peekable();
+
+    while let Some(byte) = bytes.next() {
+        let ending = match byte {
+            b'\n' => LineEnding::Lf,
+            b'\r' if bytes.next_if_eq(&b'\n').is_some() => LineEnding::CrLf,
+            b'\r' => LineEnding::Cr,
+            _ => continue,
+        };
+
+        match detected {
+            None => detected = Some(ending),
+            Some(detected) if detected != ending => return LineEnding::Mixed,
+            _ => (),
+        }
+    }
+
+    detected.unwrap_or(LineEnding::Lf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pure_styles() {
+        assert_eq!(LineEnding::Lf, detect_line_ending("[A]\na = 1\n"));
+        assert_eq!(LineEnding::CrLf, detect_line_ending("[A]\r\na = 1\r\n"));
+        assert_eq!(LineEnding::Cr, detect_line_ending("[A]\ra = 1\r"));
+        assert_eq!(LineEnding::Lf, detect_line_ending("a = 1"));
+        assert_eq!(LineEnding::Lf, detect_line_ending(""));
+    }
+
+    #[test]
+    fn mixed() {
+        assert_eq!(LineEnding::Mixed, detect_line_ending("[A]\r\na = 1\n"));
+        assert_eq!(LineEnding::Mixed, detect_line_ending("[A]\na = 1\r"));
+        assert_eq!(LineEnding::Mixed, detect_line_ending("[A]\r\r\n"));
+    }
+
+    #[test]
+    fn as_str() {
+        assert_eq!("\r\n", detect_line_ending("a\r\nb").as_str());
+    }
+}
@@ -1,30 +1,139 @@
-use crate::{Section, Value};
-use std::collections::BTreeMap;
-use std::iter::Peekable;
-use std::{error, fmt, str};
+use crate::{
+    base64, AttachedComments, CommentTarget, Comments, Dictionary, IonError, Layout, Section,
+    SectionRef, Value, ValueRef,
+};
+use std::borrow::Cow;
+use std::collections::{btree_map, BTreeMap};
+use std::fmt::Write;
+use std::iter::{self, Peekable};
+use std::ops::Range;
+use std::{error, fmt, io, mem, str};
 
 #[derive(Debug, PartialEq)]
 pub enum Element {
     Section(String),
+    ArraySection(String),
     Row(Vec<Value>),
     Entry(String, Value),
     Comment(String),
+    Directive(String, String),
+}
+
+#[derive(Clone, Copy, Default)]
+pub enum IdentRules {
+    #[default]
+    Any,
+    Identifier,
+    Custom(fn(&str) -> bool),
+}
+
+/// What `key =` with nothing but a line end or comment after `=` produces.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmptyValue {
+    #[default]
+    Error,
+    EmptyString,
+    Null,
+}
+
+/// How raw control characters (tabs, line breaks, NUL, ...) inside quoted
+/// strings are handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ControlCharPolicy {
+    #[default]
+    Allow,
+    Reject,
+    /// Replaces them with their escaped form, e.g. a tab with `\t`.
+    Escape,
+}
+
+impl IdentRules {
+    fn accepts(&self, name: &str) -> bool {
+        match self {
+            IdentRules::Any => true,
+            IdentRules::Identifier => {
+                let mut chars = name.chars();
+
+                matches!(chars.next(), Some('a'..='z' | 'A'..='Z' | '_'))
+                    && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+            }
+            IdentRules::Custom(accepts) => accepts(name),
+        }
+    }
 }
 
 pub struct Parser<'a> {
     input: &'a str,
     cur: Peekable<str::CharIndices<'a>>,
     pub(crate) errors: Vec<ParserError>,
+    pub(crate) directives: Vec<(String, String)>,
+    warnings: Vec<ParserError>,
+    pub(crate) section_arrays: BTreeMap<String, Vec<Section>>,
     accepted_sections: Option<Vec<&'a str>>,
+    section_filter: Option<Vec<&'a str>>,
     section_capacity: usize,
     row_capacity: usize,
     array_capacity: usize,
+    lenient_numbers: bool,
+    base64: bool,
+    strict_comment_column: bool,
+    section_start: usize,
+    section_end: Option<usize>,
+    ident_rules: IdentRules,
+    boolean_columns: Vec<(&'a str, Vec<usize>)>,
+    strict_boolean_columns: bool,
+    active_boolean_columns: Option<usize>,
+    section_rows: usize,
+    escape_char: char,
+    require_section_header: bool,
+    has_section_header: bool,
+    warn_trailing: bool,
+    strict_trailing: bool,
+    element_start: usize,
+    array_sections: bool,
+    special_floats: bool,
+    lazy_values: bool,
+    indent_nesting: bool,
+    whitespace_separated_arrays: bool,
+    max_line_length: Option<usize>,
+    two_pass: bool,
+    boundary_checks: bool,
+    empty_value: EmptyValue,
+    keyval_separators: Vec<&'a str>,
+    space_grouped_numbers: bool,
+    control_char_policy: ControlCharPolicy,
+    strict_strings: bool,
+    typed_cells: bool,
+    skip_separator_rows: bool,
+    merge_sections: bool,
+    max_depth: usize,
+    depth: usize,
+    strict_commas: bool,
+    case_insensitive_sections: bool,
 }
 
 impl<'a> Iterator for Parser<'a> {
     type Item = Element;
 
     fn next(&mut self) -> Option<Element> {
+        Some(match self.next_parsed(ValueRef::into_owned)? {
+            Parsed::Element(element) => element,
+            Parsed::Entry(key, value) => Element::Entry(key.to_owned(), value.into_owned()),
+            Parsed::Row(row) => Element::Row(row),
+        })
+    }
+}
+
+/// An element as read by [`Parser::next_parsed`], with the entry and the
+/// cells not yet converted to owned values.
+enum Parsed<'a, C> {
+    Element(Element),
+    Entry(&'a str, ValueRef<'a>),
+    Row(Vec<C>),
+}
+
+impl<'a> Parser<'a> {
+    fn next_parsed<C>(&mut self, cell: impl Fn(ValueRef<'a>) -> C) -> Option<Parsed<'a, C>> {
         let mut is_section_accepted = true;
 
         loop {
@@ -34,16 +143,50 @@ impl<'a> Iterator for Parser<'a> {
                 continue;
             }
 
-            let c = match self.cur.peek() {
-                Some((_, c)) => *c,
+            let (start, c) = match self.cur.peek() {
+                Some((start, c)) => (*start, *c),
                 None => return None,
             };
 
+            self.element_start = start;
+
+            if !self.is_line_within_limit(start) {
+                return None;
+            }
+
             if c == '[' {
+                self.has_section_header = true;
+                self.section_start = self.position();
+                self.section_end.get_or_insert(self.section_start);
+
+                let is_array =
+                    self.array_sections && self.slice(start + 1..self.input.len()).starts_with('[');
+
+                if is_array {
+                    self.eat('[');
+                }
+
                 let name = self.section_name();
 
+                if is_array {
+                    self.eat(']');
+                }
+
+                if !self.ident_rules.accepts(&name) {
+                    self.add_error("Invalid section name");
+                    return None;
+                }
+
                 match self.is_section_accepted(&name) {
-                    Some(true) => return Some(Element::Section(name)),
+                    Some(true) => {
+                        self.enter_section(&name);
+
+                        return Some(Parsed::Element(if is_array {
+                            Element::ArraySection(name)
+                        } else {
+                            Element::Section(name)
+                        }));
+                    }
                     Some(false) => is_section_accepted = false,
                     None => return None,
                 }
@@ -54,10 +197,33 @@ impl<'a> Iterator for Parser<'a> {
                 continue;
             }
 
+            if c == '#' {
+                if self.is_at_comment() {
+                    return self.comment().map(Parsed::Element);
+                }
+
+                self.add_error("A comment must start in column 1");
+                return None;
+            }
+
+            if c == '%' {
+                return self.directive().map(Parsed::Element);
+            }
+
+            if self.require_section_header && !self.has_section_header {
+                self.add_error("Expected a section header");
+                return None;
+            }
+
             return match c {
-                '|' => self.row(),
-                '#' => self.comment(),
-                _ => self.entry(),
+                '|' => match self.row_with(&cell)? {
+                    (_, true) if self.skip_separator_rows => continue,
+                    (row, _) => Some(Parsed::Row(row)),
+                },
+                _ => self
+                    .entry_ref()
+                    .filter(|_| self.trailing_data_allowed())
+                    .map(|(key, value)| Parsed::Entry(key, value)),
             };
         }
     }
@@ -68,34 +234,572 @@ impl<'a> Parser<'a> {
         Self::new_filtered_opt(s, None)
     }
 
+    /// Parses only the sections named in `accepted_sections`. Each name
+    /// accepts the first section with that name, and parsing stops once all
+    /// of them are found. A name ending in `*` accepts every section starting
+    /// with what precedes it, so while one is listed the whole input is read.
     pub fn new_filtered(s: &'a str, accepted_sections: Vec<&'a str>) -> Self {
         Self::new_filtered_opt(s, Some(accepted_sections))
     }
 
+    /// Validates that `bytes` are UTF-8 and parses them without copying.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<Self, str::Utf8Error> {
+        str::from_utf8(bytes).map(Self::new)
+    }
+
+    /// Points the parser at `s`, keeping its configuration, including the
+    /// section filter, and the allocations of its buffers. Errors, warnings,
+    /// directives and section arrays of the previous input are cleared.
+    pub fn reset(&mut self, s: &'a str) {
+        self.input = s;
+        self.cur = s.char_indices().peekable();
+        self.errors.clear();
+        self.directives.clear();
+        self.warnings.clear();
+        self.section_arrays.clear();
+        self.accepted_sections.clone_from(&self.section_filter);
+        self.section_start = 0;
+        self.section_end = None;
+        self.active_boolean_columns = self.boolean_columns.iter().position(|(s, _)| *s == "root");
+        self.section_rows = 0;
+        self.has_section_header = false;
+        self.element_start = 0;
+    }
+
+    /// Sets how many rows each section reserves up front. Defaults to 16.
     pub fn with_section_capacity(mut self, section_capacity: usize) -> Self {
         self.section_capacity = section_capacity;
         self
     }
 
+    /// Sets how many cells each row reserves up front. Defaults to 8.
     pub fn with_row_capacity(mut self, row_capacity: usize) -> Self {
         self.row_capacity = row_capacity;
         self
     }
 
+    /// Sets how many elements each array reserves up front. Defaults to 2.
     pub fn with_array_capacity(mut self, array_capacity: usize) -> Self {
         self.array_capacity = array_capacity;
         self
     }
 
+    /// Reads a malformed number, e.g. `12abc`, as a string up to the end of
+    /// the value instead of reporting an error. Off by default.
+    pub fn with_lenient_numbers(mut self, lenient_numbers: bool) -> Self {
+        self.lenient_numbers = lenient_numbers;
+        self
+    }
+
+    /// Reads `b64"..."` values as [`Value::Bytes`]. Off by default, where
+    /// they are errors.
+    pub fn with_base64(mut self, base64: bool) -> Self {
+        self.base64 = base64;
+        self
+    }
+
+    /// Only a `#` in column 1 starts a comment, a `#` in a row is part of the
+    /// cell. Any other `#` is reported as an error, or after a value, as
+    /// trailing data when [`Parser::with_warn_trailing`] or
+    /// [`Parser::with_strict_trailing`] is enabled.
+    pub fn with_strict_comment_column(mut self, strict_comment_column: bool) -> Self {
+        self.strict_comment_column = strict_comment_column;
+        self
+    }
+
+    /// Restricts which section names and keys are accepted, anything else is
+    /// reported as an error. Defaults to [`IdentRules::Any`].
+    pub fn with_identifier_rules(mut self, ident_rules: IdentRules) -> Self {
+        self.ident_rules = ident_rules;
+        self
+    }
+
+    /// Parses the given columns of `section` ("root" for rows before any
+    /// header) as `Value::Boolean`. Cells other than `true`/`false` are kept
+    /// as strings unless [`Parser::with_strict_boolean_columns`] is enabled.
+    pub fn with_boolean_columns(mut self, section: &'a str, columns: &[usize]) -> Self {
+        if section == "root" {
+            self.active_boolean_columns = Some(self.boolean_columns.len());
+        }
+
+        self.boolean_columns.push((section, columns.to_vec()));
+        self
+    }
+
+    /// Reports an error for boolean column cells that are neither `true` nor
+    /// `false`. Empty cells, the first row of a section (the header) and
+    /// `---` separator cells are exempt.
+    pub fn with_strict_boolean_columns(mut self, strict_boolean_columns: bool) -> Self {
+        self.strict_boolean_columns = strict_boolean_columns;
+        self
+    }
+
+    /// Sets the character that starts an escape in strings and cells. Defaults
+    /// to `\`.
+    pub fn with_escape_char(mut self, escape_char: char) -> Self {
+        self.escape_char = escape_char;
+        self
+    }
+
+    /// Sets how raw control characters in quoted strings are handled. Defaults
+    /// to [`ControlCharPolicy::Allow`].
+    pub fn with_control_char_policy(mut self, control_char_policy: ControlCharPolicy) -> Self {
+        self.control_char_policy = control_char_policy;
+        self
+    }
+
+    /// Rejects strings missing their closing quote. By default such a string
+    /// runs to the end of the input.
+    pub fn with_strict_strings(mut self, strict_strings: bool) -> Self {
+        self.strict_strings = strict_strings;
+        self
+    }
+
+    /// Reads table cells holding a number or `true`/`false` as
+    /// `Value::Integer`, `Value::Float` or `Value::Boolean`, like entry
+    /// values. Other cells, including empty ones, stay strings.
+    pub fn with_typed_cells(mut self, typed_cells: bool) -> Self {
+        self.typed_cells = typed_cells;
+        self
+    }
+
+    /// Drops Markdown separator rows such as `|---|:--:|`. Note that
+    /// [`Section::rows_without_header`] relies on that row to tell the header
+    /// apart, so it then returns all rows.
+    pub fn with_skip_separator_rows(mut self, skip_separator_rows: bool) -> Self {
+        self.skip_separator_rows = skip_separator_rows;
+        self
+    }
+
+    /// Limits how deeply arrays and dictionaries may nest, 128 by default.
+    /// Deeper values are reported as an error instead of overflowing the
+    /// stack.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Reports leading, doubled and trailing commas in arrays and
+    /// dictionaries, e.g. `[,1]`, `[1,,2]` and `[1,]`, as errors.
+    pub fn with_strict_commas(mut self, strict_commas: bool) -> Self {
+        self.strict_commas = strict_commas;
+        self
+    }
+
+    /// Matches the sections passed to [`Parser::new_filtered`] ignoring ASCII
+    /// case. Sections keep the names they have in the input.
+    pub fn with_case_insensitive_sections(mut self, case_insensitive_sections: bool) -> Self {
+        self.case_insensitive_sections = case_insensitive_sections;
+        self
+    }
+
+    /// Merges a repeated `[SECTION]` into the earlier one instead of
+    /// replacing it: its entries are added, overwriting entries with the same
+    /// key, and its rows are appended.
+    pub fn with_merge_sections(mut self, merge_sections: bool) -> Self {
+        self.merge_sections = merge_sections;
+        self
+    }
+
+    /// Reports entries and rows before the first section header as an error
+    /// instead of reading them into `root`. Off by default.
+    pub fn with_require_section_header(mut self, require_section_header: bool) -> Self {
+        self.require_section_header = require_section_header;
+        self
+    }
+
+    /// Reports data after a value on the same line, other than a comment, as
+    /// a warning. Off by default, where it is ignored.
+    pub fn with_warn_trailing(mut self, warn_trailing: bool) -> Self {
+        self.warn_trailing = warn_trailing;
+        self
+    }
+
+    /// Reports data after a value on the same line, other than a comment, as
+    /// an error. Off by default, where it is ignored.
+    pub fn with_strict_trailing(mut self, strict_trailing: bool) -> Self {
+        self.strict_trailing = strict_trailing;
+        self
+    }
+
+    /// Reads `[[name]]` headers as repeated sections, collected in
+    /// [`Parser::section_arrays`]. Off by default.
+    pub fn with_array_sections(mut self, array_sections: bool) -> Self {
+        self.array_sections = array_sections;
+        self
+    }
+
+    /// Reads `inf`, `-inf` and `nan` as floats. Off by default, where they are
+    /// errors.
+    pub fn with_special_floats(mut self, special_floats: bool) -> Self {
+        self.special_floats = special_floats;
+        self
+    }
+
+    /// Keeps the byte span of every entry value as [`Value::Lazy`] instead
+    /// of parsing it, see [`Value::resolve`]. Lazy values must be resolved
+    /// before writing a document, they are written as `lazy(lo..hi)`
+    /// placeholders that do not read back.
+    pub fn with_lazy_values(mut self, lazy_values: bool) -> Self {
+        self.lazy_values = lazy_values;
+        self
+    }
+
+    /// Reads a key with nothing after it as a dictionary of the entries
+    /// indented below it. Off by default.
+    pub fn with_indent_nesting(mut self, indent_nesting: bool) -> Self {
+        self.indent_nesting = indent_nesting;
+        self
+    }
+
+    /// Accepts array elements separated by whitespace only, e.g. `[1 2]`.
+    pub fn with_whitespace_separated_arrays(mut self, whitespace_separated_arrays: bool) -> Self {
+        self.whitespace_separated_arrays = whitespace_separated_arrays;
+        self
+    }
+
+    /// Reads an entry value like `1 000 000` as one integer.
+    ///
+    /// Spaces also separate tokens, so this is ambiguous and deliberately
+    /// narrow: it applies only to a whole entry value, the first group has
+    /// one to three digits, every further group exactly three digits after a
+    /// single space, and nothing but a comment may follow on the line. Values
+    /// in arrays, dictionaries and rows are never grouped.
+    pub fn with_space_grouped_numbers(mut self, space_grouped_numbers: bool) -> Self {
+        self.space_grouped_numbers = space_grouped_numbers;
+        self
+    }
+
+    /// Rejects lines, and strings spanning several lines, longer than
+    /// `max_line_length` bytes.
+    pub fn with_max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = Some(max_line_length);
+        self
+    }
+
+    /// Scans the input once before parsing to size every section's rows
+    /// exactly, instead of using the section capacity for all of them.
+    ///
+    /// The scan costs about as much as the reallocations it saves on a
+    /// typical document, so this only pays off when some tables have far
+    /// more rows than the section capacity.
+    pub fn with_two_pass(mut self, two_pass: bool) -> Self {
+        self.two_pass = two_pass;
+        self
+    }
+
+    /// Reports slicing the input off char boundaries as an error, or panics
+    /// in debug builds. Off by default.
+    pub fn with_boundary_checks(mut self, boundary_checks: bool) -> Self {
+        self.boundary_checks = boundary_checks;
+        self
+    }
+
+    /// Sets what `key =` with no value produces. Defaults to
+    /// [`EmptyValue::Error`].
+    pub fn with_empty_value(mut self, empty_value: EmptyValue) -> Self {
+        self.empty_value = empty_value;
+        self
+    }
+
+    /// Replaces the accepted key-value separators (`=` by default). The
+    /// longest matching separator wins, so `=>` is not read as `=`.
+    pub fn with_keyval_separators(mut self, separators: &[&'a str]) -> Self {
+        self.keyval_separators = separators.to_vec();
+        self.keyval_separators
+            .sort_by_key(|separator| std::cmp::Reverse(separator.len()));
+        self
+    }
+
+    pub fn section_arrays(&self) -> &BTreeMap<String, Vec<Section>> {
+        &self.section_arrays
+    }
+
+    pub fn warnings(&self) -> &[ParserError] {
+        &self.warnings
+    }
+
+    pub fn errors(&self) -> &[ParserError] {
+        &self.errors
+    }
+
+    pub fn nth_section(s: &'a str, name: &str, n: usize) -> Option<Section> {
+        let mut parser = Self::new(s);
+        let mut occurrences = 0;
+        let mut section = None;
+
+        while let Some(element) = parser.next() {
+            match (element, &mut section) {
+                (Element::Section(_) | Element::ArraySection(_), Some(_)) => break,
+                (Element::Section(section_name), None) if section_name == name => {
+                    if occurrences == n {
+                        section = Some(Section::with_capacity(parser.section_capacity));
+                    }
+
+                    occurrences += 1;
+                }
+                (Element::Row(row), Some(section)) => section.rows.push(row),
+                (Element::Entry(key, value), Some(section)) => {
+                    section.dictionary.insert(key, value);
+                }
+                _ => continue,
+            }
+        }
+
+        if parser.errors.is_empty() {
+            section
+        } else {
+            None
+        }
+    }
+
+    /// Reads just the entries of section `name`, ignoring its rows. Parsing
+    /// stops at the header following the section.
+    pub fn read_section_map(s: &'a str, name: &'a str) -> Option<BTreeMap<String, Value>> {
+        let mut parser = Self::new_filtered(s, vec![name]);
+        let mut dictionary = None;
+
+        for element in parser.by_ref() {
+            match (element, &mut dictionary) {
+                (Element::Section(_), None) => dictionary = Some(BTreeMap::new()),
+                (Element::Entry(key, value), Some(dictionary)) => {
+                    dictionary.insert(key, value);
+                }
+                _ => continue,
+            }
+        }
+
+        if parser.errors.is_empty() {
+            dictionary
+        } else {
+            None
+        }
+    }
+
+    /// Parses a stream of documents separated by `---` lines, see
+    /// [`Parser::documents_with_delimiter`].
+    pub fn documents(
+        s: &'a str,
+    ) -> impl Iterator<Item = Result<BTreeMap<String, Section>, Vec<ParserError>>> + 'a {
+        Self::documents_with_delimiter(s, "---")
+    }
+
+    /// Splits `s` on lines consisting of just `delimiter` and parses every
+    /// chunk as an independent document. Error offsets are relative to `s`.
+    /// A blank chunk after the last delimiter is not a document, so the
+    /// stream may end with a delimiter.
+    pub fn documents_with_delimiter(
+        s: &'a str,
+        delimiter: &'a str,
+    ) -> impl Iterator<Item = Result<BTreeMap<String, Section>, Vec<ParserError>>> + 'a {
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        let mut offset = 0;
+
+        for line in s.split_inclusive('\n') {
+            if line.trim() == delimiter {
+                chunks.push(start..offset);
+                start = offset + line.len();
+            }
+
+            offset += line.len();
+        }
+
+        if chunks.is_empty() || !s[start..].trim().is_empty() {
+            chunks.push(start..s.len());
+        }
+
+        chunks.into_iter().map(move |chunk| {
+            let mut parser = Parser::new(&s[chunk.clone()]);
+
+            parser.read().ok_or_else(|| {
+                parser
+                    .errors
+                    .into_iter()
+                    .map(|error| {
+                        ParserError::new(
+                            s,
+                            chunk.start + error.lo,
+                            chunk.start + error.hi,
+                            error.desc,
+                        )
+                    })
+                    .collect()
+            })
+        })
+    }
+
+    pub fn elements_or_errors(mut self) -> impl Iterator<Item = Result<Element, ParserError>> + 'a {
+        let mut reported = 0;
+        let mut pending = None;
+        let mut finished = false;
+
+        iter::from_fn(move || {
+            loop {
+                if reported < self.errors.len() {
+                    reported += 1;
+                    return Some(Err(self.errors[reported - 1].clone()));
+                }
+
+                if let Some(element) = pending.take() {
+                    return Some(Ok(element));
+                }
+
+                if finished {
+                    return None;
+                }
+
+                pending = self.next();
+                finished = pending.is_none();
+            }
+        })
+    }
+
+    /// Yields one complete section at a time, so only the section being read
+    /// is held in memory. Entries and rows before the first header are
+    /// yielded as "root" when present, or when the document has no sections
+    /// and no filter is set. A parse error is yielded in place of the section
+    /// it occurs in and ends the iteration.
+    pub fn section_chunks(
+        mut self,
+    ) -> impl Iterator<Item = Result<(String, Section), ParserError>> + 'a {
+        let mut name: Option<String> = None;
+        let mut section = Section::with_capacity(self.section_capacity);
+        let mut finished = false;
+
+        iter::from_fn(move || {
+            while !finished {
+                let element = self.next();
+
+                if let Some(error) = self.errors.first() {
+                    finished = true;
+                    return Some(Err(error.clone()));
+                }
+
+                let next_name = match element {
+                    Some(Element::Section(n) | Element::ArraySection(n)) => Some(n),
+                    Some(Element::Row(row)) => {
+                        section.rows.push(row);
+                        continue;
+                    }
+                    Some(Element::Entry(key, value)) => {
+                        section.dictionary.insert(key, value);
+                        continue;
+                    }
+                    Some(Element::Directive(n, args)) => {
+                        self.directives.push((n, args));
+                        continue;
+                    }
+                    Some(Element::Comment(_)) => continue,
+                    None => None,
+                };
+
+                finished = next_name.is_none();
+
+                let is_root = name.is_none();
+                let chunk_name = mem::replace(&mut name, next_name);
+                let chunk =
+                    mem::replace(&mut section, Section::with_capacity(self.section_capacity));
+
+                let is_root_yielded = self.accepted_sections.is_none()
+                    && (!chunk.dictionary.is_empty() || !chunk.rows.is_empty() || finished);
+
+                if !is_root || is_root_yielded {
+                    let chunk_name = chunk_name.unwrap_or_else(|| "root".to_owned());
+                    return Some(Ok((chunk_name, chunk)));
+                }
+            }
+
+            None
+        })
+    }
+
+    /// Yields every element together with the byte range of its source text,
+    /// see [`element_source`]. The range covers the element's delimiters but
+    /// not the whitespace or line break following it.
+    pub fn spanned(mut self) -> impl Iterator<Item = (Element, Range<usize>)> + 'a {
+        iter::from_fn(move || {
+            let element = self.next()?;
+            let start = self.element_start;
+            let end = start + self.input[start..self.position()].trim_end().len();
+
+            Some((element, start..end))
+        })
+    }
+
+    pub fn section_spans(mut self) -> Vec<(String, Range<usize>)> {
+        let mut spans = Vec::new();
+        let mut current: Option<(String, usize)> = None;
+
+        while let Some(element) = self.next() {
+            if let Element::Section(name) | Element::ArraySection(name) = element {
+                let end = self.section_end.take();
+
+                if let Some((name, start)) = current.take() {
+                    spans.push((name, start..end.unwrap_or(self.section_start)));
+                }
+
+                current = Some((name, self.section_start));
+            }
+        }
+
+        if let Some((name, start)) = current {
+            let end = self.section_end.unwrap_or(self.input.len());
+            spans.push((name, start..end));
+        }
+
+        spans
+    }
+
     fn new_filtered_opt(s: &'a str, accepted_sections: Option<Vec<&'a str>>) -> Self {
         Self {
             input: s,
             cur: s.char_indices().peekable(),
             errors: Vec::new(),
+            directives: Vec::new(),
+            warnings: Vec::new(),
+            section_arrays: BTreeMap::new(),
+            section_filter: accepted_sections.clone(),
             accepted_sections,
             section_capacity: 16,
             row_capacity: 8,
             array_capacity: 2,
+            lenient_numbers: false,
+            base64: false,
+            strict_comment_column: false,
+            section_start: 0,
+            section_end: None,
+            ident_rules: IdentRules::Any,
+            boolean_columns: Vec::new(),
+            strict_boolean_columns: false,
+            active_boolean_columns: None,
+            section_rows: 0,
+            escape_char: '\\',
+            require_section_header: false,
+            has_section_header: false,
+            warn_trailing: false,
+            strict_trailing: false,
+            element_start: 0,
+            array_sections: false,
+            special_floats: false,
+            lazy_values: false,
+            indent_nesting: false,
+            whitespace_separated_arrays: false,
+            max_line_length: None,
+            two_pass: false,
+            boundary_checks: false,
+            empty_value: EmptyValue::Error,
+            keyval_separators: vec!["="],
+            space_grouped_numbers: false,
+            control_char_policy: ControlCharPolicy::Allow,
+            strict_strings: false,
+            typed_cells: false,
+            skip_separator_rows: false,
+            merge_sections: false,
+            max_depth: 128,
+            depth: 0,
+            strict_commas: false,
+            case_insensitive_sections: false,
         }
     }
 
@@ -125,7 +829,7 @@ impl<'a> Parser<'a> {
     }
 
     fn skip_line(&mut self) {
-        self.cur.by_ref().find(|&(_, c)| c != '\n');
+        self.cur.by_ref().find(|&(_, c)| c == '\n');
     }
 
     fn comment(&mut self) -> Option<Element> {
@@ -138,6 +842,36 @@ impl<'a> Parser<'a> {
         ))
     }
 
+    fn directive(&mut self) -> Option<Element> {
+        if !self.eat('%') {
+            return None;
+        }
+
+        let name = self
+            .slice_while(|ch| !ch.is_whitespace())
+            .unwrap_or_default()
+            .to_owned();
+        self.whitespace();
+        let args = self
+            .slice_while(|ch| !matches!(ch, '\n' | '\r'))
+            .map(str::trim_end)
+            .unwrap_or_default()
+            .to_owned();
+
+        Some(Element::Directive(name, args))
+    }
+
+    fn is_at_comment(&mut self) -> bool {
+        let pos = match self.cur.peek() {
+            Some((pos, '#')) => *pos,
+            _ => return false,
+        };
+
+        !self.strict_comment_column
+            || pos == 0
+            || matches!(self.input.as_bytes()[pos - 1], b'\n' | b'\r')
+    }
+
     fn eat(&mut self, ch: char) -> bool {
         match self.cur.peek() {
             Some((_, c)) if *c == ch => {
@@ -160,22 +894,161 @@ impl<'a> Parser<'a> {
     }
 
     fn entry(&mut self) -> Option<Element> {
+        self.entry_ref()
+            .map(|(key, value)| Element::Entry(key.to_owned(), value.into_owned()))
+    }
+
+    fn entry_ref(&mut self) -> Option<(&'a str, ValueRef<'a>)> {
+        let key_start = self.position();
+
         if let Some(key) = self.key_name() {
+            if !self.ident_rules.accepts(key) {
+                self.add_error("Invalid key");
+                return None;
+            }
+
+            if self.indent_nesting && self.is_at_line_end() {
+                let indent = key_start - self.input[..key_start].rfind('\n').map_or(0, |i| i + 1);
+
+                return self
+                    .nested(|p| p.indented_dictionary(indent))
+                    .map(|val| (key, ValueRef::Owned(val)));
+            }
+
             if !self.keyval_sep() {
+                let expected = self
+                    .keyval_separators
+                    .iter()
+                    .map(|separator| format!("'{separator}'"))
+                    .collect::<Vec<_>>()
+                    .join(" or ");
+                self.add_error(&format!("Expected {expected}"));
                 return None;
             }
 
-            if let Some(val) = self.value() {
-                return Some(Element::Entry(key, val));
+            if self.empty_value != EmptyValue::Error
+                && (self.is_at_line_end() || self.is_at_comment())
+            {
+                let val = match self.empty_value {
+                    EmptyValue::Null => ValueRef::Owned(Value::Null),
+                    _ => ValueRef::String(Cow::Borrowed("")),
+                };
+
+                return Some((key, val));
+            }
+
+            let val = if self.lazy_values {
+                self.lazy_value().map(ValueRef::Owned)
+            } else if self.space_grouped_numbers {
+                self.space_grouped_number()
+                    .map(ValueRef::Owned)
+                    .or_else(|| self.value_ref())
+            } else {
+                self.value_ref()
+            };
+
+            if let Some(val) = val {
+                return Some((key, val));
             }
+        } else {
+            self.add_error("Expected a key");
         }
 
         None
     }
 
-    fn key_name(&mut self) -> Option<String> {
-        self.slice_while(|ch| matches!(ch, 'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-'))
-            .map(str::to_owned)
+    fn is_at_line_end(&mut self) -> bool {
+        self.whitespace();
+        matches!(self.cur.peek(), None | Some((_, '\n' | '\r')))
+    }
+
+    /// Reads the entries indented deeper than `indent` as one dictionary.
+    /// Indentation is counted in spaces only and every entry of the block
+    /// must use the same width. Blank and comment lines are skipped.
+    fn indented_dictionary(&mut self, indent: usize) -> Option<Value> {
+        let mut dictionary = BTreeMap::new();
+        let mut width = None;
+
+        while let Some((line_indent, content_start)) = self.next_line_indent() {
+            if line_indent <= indent {
+                break;
+            }
+
+            while self.position() < content_start {
+                self.cur.next();
+            }
+
+            if self.input[content_start - line_indent..content_start].contains('\t') {
+                self.add_error("Indentation must use spaces");
+                return None;
+            }
+
+            if *width.get_or_insert(line_indent) != line_indent {
+                self.add_error("Inconsistent indentation");
+                return None;
+            }
+
+            let errors = self.errors.len();
+
+            match self.entry() {
+                Some(Element::Entry(key, value)) => {
+                    dictionary.insert(key, value);
+                }
+                _ => {
+                    if self.errors.len() == errors {
+                        self.add_error("Cannot read an indented entry");
+                    }
+
+                    return None;
+                }
+            }
+        }
+
+        Some(Value::Dictionary(dictionary))
+    }
+
+    /// Looks past the end of the current line for the next line with content
+    /// and returns its indentation width and the offset of its first char.
+    fn next_line_indent(&mut self) -> Option<(usize, usize)> {
+        let mut offset = self.position();
+        let mut lines = self.input[offset..].split_inclusive('\n');
+        offset += lines.next()?.len();
+
+        for line in lines {
+            let content = line.trim_start_matches([' ', '\t']);
+
+            if !content.trim().is_empty() && !content.starts_with('#') {
+                let indent = line.len() - content.len();
+                return Some((indent, offset + indent));
+            }
+
+            offset += line.len();
+        }
+
+        None
+    }
+
+    fn key_name(&mut self) -> Option<&'a str> {
+        if !matches!(self.ident_rules, IdentRules::Any) {
+            return self.slice_while(|ch| !matches!(ch, ' ' | '\t' | '\n' | '\r' | '='));
+        }
+
+        self.slice_while(|ch| matches!(ch, 'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-'))
+    }
+
+    /// Like [`Parser::value`], but borrows a single-line string from the
+    /// input unless it has escapes.
+    fn value_ref(&mut self) -> Option<ValueRef<'a>> {
+        self.whitespace();
+        self.newline();
+        self.whitespace();
+
+        match self.cur.peek() {
+            Some((pos, '"')) if !self.input[*pos..].starts_with(r#"""""#) => {
+                self.string().map(ValueRef::String)
+            }
+            _ => self.value().map(ValueRef::Owned),
+        }
     }
 
     fn value(&mut self) -> Option<Value> {
@@ -184,14 +1057,27 @@ impl<'a> Parser<'a> {
         self.whitespace();
 
         match self.cur.peek() {
+            Some((pos, '"')) if self.input[*pos..].starts_with(r#"""""#) => self.multiline_string(),
             Some((_, '"')) => self.finish_string(),
-            Some((_, '[')) => self.finish_array(),
-            Some((_, '{')) => self.finish_dictionary(),
+            Some((_, '[')) => self.nested(Self::finish_array),
+            Some((_, '{')) => self.nested(Self::finish_dictionary),
             Some((_, ch)) if ch.is_ascii_digit() => self.number(),
             Some((pos, 't')) | Some((pos, 'f')) => {
                 let pos = *pos;
                 self.boolean(pos)
             }
+            Some((pos, 'n')) if self.input[*pos..].starts_with("null") => {
+                let pos = *pos;
+                self.null(pos)
+            }
+            Some((pos, 'b')) if self.base64 => {
+                let pos = *pos;
+                self.bytes(pos)
+            }
+            Some((pos, 'i' | 'n' | '-')) if self.special_floats => {
+                let pos = *pos;
+                self.special_float(pos)
+            }
             _ => {
                 self.add_error("Cannot read a value");
                 None
@@ -199,10 +1085,66 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Reads an array or dictionary one nesting level deeper.
+    fn nested(&mut self, container: impl FnOnce(&mut Self) -> Option<Value>) -> Option<Value> {
+        if self.depth == self.max_depth {
+            let max_depth = self.max_depth;
+            self.add_error(&format!(
+                "Exceeded the maximum nesting depth of {max_depth}"
+            ));
+            return None;
+        }
+
+        self.depth += 1;
+        let value = container(self);
+        self.depth -= 1;
+        value
+    }
+
+    fn lazy_value(&mut self) -> Option<Value> {
+        self.whitespace();
+        self.newline();
+        self.whitespace();
+
+        let start = self.position();
+        let mut depth = 0usize;
+
+        while let Some(&(_, ch)) = self.cur.peek() {
+            match ch {
+                '"' if self.input[self.position()..].starts_with(r#"""""#) => {
+                    self.multiline_string()?;
+                    continue;
+                }
+                '"' => {
+                    self.cur.next();
+                    self.slice_to_excluding('"');
+                    continue;
+                }
+                '[' | '{' => depth += 1,
+                ']' | '}' if depth > 0 => depth -= 1,
+                ' ' | '\t' | '\n' | '\r' | ',' | ']' | '}' | '#' if depth == 0 => break,
+                _ => (),
+            }
+
+            self.cur.next();
+        }
+
+        let end = self.position();
+
+        if start == end {
+            self.add_error("Cannot read a value");
+            return None;
+        }
+
+        Some(Value::Lazy(start..end))
+    }
+
     fn finish_array(&mut self) -> Option<Value> {
         self.cur.next();
 
         let mut row = Vec::with_capacity(self.array_capacity);
+        let mut needs_separator = false;
+        let mut trailing_comma = None;
 
         loop {
             self.whitespace();
@@ -210,15 +1152,34 @@ impl<'a> Parser<'a> {
             if let Some((_, ch)) = self.cur.peek() {
                 match ch {
                     ']' => {
+                        if let Some(pos) = trailing_comma.filter(|_| self.strict_commas) {
+                            self.push_error(pos, pos + 1, "Trailing ','");
+                            break;
+                        }
+
                         self.cur.next();
                         return Some(Value::Array(row));
                     }
+                    ',' if self.strict_commas && !needs_separator => {
+                        self.add_error("Unexpected ','");
+                        break;
+                    }
                     ',' => {
+                        trailing_comma = Some(self.position());
                         self.cur.next();
+                        needs_separator = false;
                         continue;
                     }
+                    _ if needs_separator && !self.whitespace_separated_arrays => {
+                        self.add_error("Expected ',' or ']'");
+                        break;
+                    }
                     _ => match self.value() {
-                        Some(v) => row.push(v),
+                        Some(v) => {
+                            row.push(v);
+                            needs_separator = true;
+                            trailing_comma = None;
+                        }
                         None => break,
                     },
                 }
@@ -234,6 +1195,8 @@ impl<'a> Parser<'a> {
     fn finish_dictionary(&mut self) -> Option<Value> {
         self.cur.next();
         let mut map = BTreeMap::new();
+        let mut after_entry = false;
+        let mut trailing_comma = None;
 
         loop {
             self.whitespace();
@@ -241,23 +1204,41 @@ impl<'a> Parser<'a> {
             if let Some((_, ch)) = self.cur.peek() {
                 match ch {
                     '}' => {
+                        if let Some(pos) = trailing_comma.filter(|_| self.strict_commas) {
+                            self.push_error(pos, pos + 1, "Trailing ','");
+                            break;
+                        }
+
                         self.cur.next();
                         return Some(Value::Dictionary(map));
                     }
+                    ',' if self.strict_commas && !after_entry => {
+                        self.add_error("Unexpected ','");
+                        break;
+                    }
                     ',' => {
+                        trailing_comma = Some(self.position());
                         self.cur.next();
+                        after_entry = false;
                         continue;
                     }
                     '\n' => {
                         self.cur.next();
                         continue;
                     }
+                    '#' => {
+                        self.comment();
+                        continue;
+                    }
                     _ => {
                         match self.entry() {
                             Some(Element::Entry(k, v)) => map.insert(k, v),
                             None => break,
                             _ => panic!("Element::Entry expected"),
                         };
+
+                        after_entry = true;
+                        trailing_comma = None;
                     }
                 }
             } else {
@@ -270,21 +1251,137 @@ impl<'a> Parser<'a> {
     }
 
     fn number(&mut self) -> Option<Value> {
+        let start = self.position();
+
+        match self.input.get(start..start + 2) {
+            Some("0x" | "0X") => return self.radix_number(16, "Cannot read a hexadecimal number"),
+            Some("0o" | "0O") => return self.radix_number(8, "Cannot read an octal number"),
+            Some("0b" | "0B") => return self.radix_number(2, "Cannot read a binary number"),
+            _ => (),
+        }
+
+        let value = self.decimal_number();
+
+        if value.is_some() && self.is_at_value_end() {
+            return value;
+        }
+
+        if self.lenient_numbers {
+            while !self.is_at_value_end() {
+                self.cur.next();
+            }
+
+            let end = self.position();
+            return Some(Value::String(self.slice(start..end).to_owned()));
+        }
+
+        let end = self.position();
+        let text = self.slice(start..end);
+
+        if text.ends_with('_') || text.contains("__") || text.contains("._") {
+            self.add_error("Invalid digit separator");
+        } else if text.ends_with(['e', 'E', '+', '-']) {
+            self.add_error("Cannot read an exponent");
+        } else {
+            self.add_error("Cannot read a number");
+        }
+
+        None
+    }
+
+    /// Reads an integer after a `0x`, `0o` or `0b` prefix. Errors point at
+    /// the first char that is not a digit of `radix`.
+    fn radix_number(&mut self, radix: u32, error: &str) -> Option<Value> {
+        self.cur.next();
+        self.cur.next();
+
+        let value = self
+            .slice_while(|ch| ch.is_digit(radix))
+            .and_then(|digits| i64::from_str_radix(digits, radix).ok());
+
+        match value {
+            Some(value) if self.is_at_value_end() => Some(Value::Integer(value)),
+            _ => {
+                self.add_error(error);
+                None
+            }
+        }
+    }
+
+    fn space_grouped_number(&mut self) -> Option<Value> {
+        self.whitespace();
+
+        let rest = &self.input[self.position()..];
+        let digits_at = |offset: usize| {
+            rest[offset..]
+                .bytes()
+                .take_while(u8::is_ascii_digit)
+                .count()
+        };
+
+        let mut len = digits_at(0);
+
+        if !(1..=3).contains(&len) {
+            return None;
+        }
+
+        let first_group = len;
+
+        while rest[len..].starts_with(' ') && digits_at(len + 1) == 3 {
+            len += 4;
+        }
+
+        let tail = rest[len..].trim_start_matches([' ', '\t']);
+
+        if len == first_group || !matches!(tail.chars().next(), None | Some('\n' | '\r' | '#')) {
+            return None;
+        }
+
+        let value = rest[..len].replace(' ', "").parse().ok()?;
+
+        for _ in 0..len {
+            self.cur.next();
+        }
+
+        Some(Value::Integer(value))
+    }
+
+    fn decimal_number(&mut self) -> Option<Value> {
         let mut is_float = false;
         let prefix = self.integer()?;
 
         let decimal = if self.eat('.') {
             is_float = true;
-            Some(self.integer())?
+            let from = self.position();
+            let decimal = self.integer();
+
+            if decimal.is_none() && self.position() > from {
+                return None;
+            }
+
+            decimal
         } else {
             None
         };
 
-        let input = match &decimal {
+        let mut input = match &decimal {
             Some(decimal) => prefix + "." + decimal,
             None => prefix,
         };
 
+        if let Some(&(_, e @ ('e' | 'E'))) = self.cur.peek() {
+            self.cur.next();
+            is_float = true;
+            input.push(e);
+
+            if let Some(&(_, sign @ ('+' | '-'))) = self.cur.peek() {
+                self.cur.next();
+                input.push(sign);
+            }
+
+            input.push_str(&self.integer()?);
+        }
+
         if is_float {
             input.parse().ok().map(Value::Float)
         } else {
@@ -292,457 +1389,3260 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Reads a digit run, allowing single `_` separators between digits.
     fn integer(&mut self) -> Option<String> {
-        self.slice_while(|ch| ch.is_ascii_digit())
-            .map(str::to_owned)
-    }
+        let digits = self.slice_while(|ch| ch.is_ascii_digit() || ch == '_')?;
 
-    fn boolean(&mut self, start: usize) -> Option<Value> {
-        let rest = &self.input[start..];
+        if !digits.contains('_') {
+            return Some(digits.to_owned());
+        }
 
-        if rest.starts_with("true") {
-            for _ in 0..4 {
-                self.cur.next();
-            }
+        if digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+            return None;
+        }
 
-            Some(Value::Boolean(true))
-        } else if rest.starts_with("false") {
-            for _ in 0..5 {
-                self.cur.next();
-            }
+        Some(digits.replace('_', ""))
+    }
 
+    fn boolean(&mut self, start: usize) -> Option<Value> {
+        if self.keyword(start, "true") {
+            Some(Value::Boolean(true))
+        } else if self.keyword(start, "false") {
             Some(Value::Boolean(false))
         } else {
+            self.add_error("Cannot read a value");
             None
         }
     }
 
-    fn finish_string(&mut self) -> Option<Value> {
-        self.cur.next();
+    fn null(&mut self, start: usize) -> Option<Value> {
+        if self.keyword(start, "null") {
+            return Some(Value::Null);
+        }
 
-        self.slice_to_excluding('"')
-            .map(|s| {
-                s.replace("\\\\", "\\")
-                    .replace("\\n", "\n")
-                    .replace("\\\"", "\"")
-            })
-            .map(Value::String)
+        self.add_error("Cannot read a value");
+        None
     }
 
-    fn keyval_sep(&mut self) -> bool {
-        self.whitespace();
+    /// Consumes `literal` if the input at `start` is exactly that token, i.e.
+    /// it is followed by a value terminator or the end of input.
+    fn keyword(&mut self, start: usize, literal: &str) -> bool {
+        let rest = self.slice(start..self.input.len());
+        let is_token = rest.strip_prefix(literal).map_or(false, |after| {
+            after.chars().next().map_or(true, is_value_terminator)
+        });
 
-        if !self.expect('=') {
-            return false;
+        if is_token {
+            for _ in 0..literal.len() {
+                self.cur.next();
+            }
         }
 
-        self.whitespace();
-        true
-    }
-
-    fn expect(&mut self, ch: char) -> bool {
-        self.eat(ch)
+        is_token
     }
 
-    fn row(&mut self) -> Option<Element> {
-        let mut row = Vec::with_capacity(self.row_capacity);
+    fn bytes(&mut self, start: usize) -> Option<Value> {
+        if !self.slice(start..self.input.len()).starts_with("b64\"") {
+            self.add_error("Cannot read a value");
+            return None;
+        }
 
-        self.eat('|');
+        for _ in 0..4 {
+            self.cur.next();
+        }
 
-        loop {
-            self.whitespace();
+        let encoded = self.slice_to_excluding('"').unwrap_or_default();
 
-            if self.comment().is_some() {
-                break;
+        match base64::decode(encoded) {
+            Some(bytes) => Some(Value::Bytes(bytes)),
+            None => {
+                self.add_error("Cannot decode base64");
+                None
             }
+        }
+    }
 
-            if self.newline() {
-                break;
+    fn special_float(&mut self, start: usize) -> Option<Value> {
+        let rest = self.slice(start..self.input.len());
+        let literal = [
+            ("inf", f64::INFINITY),
+            ("-inf", f64::NEG_INFINITY),
+            ("nan", f64::NAN),
+        ]
+        .into_iter()
+        .find(|(literal, _)| rest.starts_with(literal));
+
+        if let Some((literal, value)) = literal {
+            for _ in 0..literal.len() {
+                self.cur.next();
             }
 
-            if self.cur.peek().is_none() {
-                break;
+            if self.is_at_value_end() {
+                return Some(Value::Float(value));
             }
-
-            row.push(Value::String(self.cell()));
         }
 
-        Some(Element::Row(row))
+        self.add_error("Cannot read a value");
+        None
     }
 
-    fn cell(&mut self) -> String {
-        self.whitespace();
-
-        self.slice_to_excluding('|')
-            .map(str::trim_end)
-            .unwrap_or_default()
-            .replace("\\\\", "\\")
-            .replace("\\n", "\n")
-            .replace("\\|", "|")
+    fn finish_string(&mut self) -> Option<Value> {
+        self.string().map(|s| Value::String(s.into_owned()))
     }
 
-    pub fn read(&mut self) -> Option<BTreeMap<String, Section>> {
-        let mut map = BTreeMap::new();
-        let mut section = Section::with_capacity(self.section_capacity);
-        let mut name = None;
+    fn string(&mut self) -> Option<Cow<'a, str>> {
+        let start = self.position();
+        self.cur.next();
 
-        while let Some(el) = self.next() {
-            match el {
-                Element::Section(n) => {
-                    if let Some(name) = name {
-                        map.insert(name, section);
-                    }
-                    name = Some(n);
-                    section = Section::with_capacity(self.section_capacity);
-                }
-                Element::Row(row) => section.rows.push(row),
-                Element::Entry(key, value) => {
-                    section.dictionary.insert(key, value);
-                }
-                _ => continue,
-            }
-        }
+        let escape_char = self.escape_char;
+        let escape_controls = self.control_char_policy == ControlCharPolicy::Escape;
+        let raw = self.slice_to_excluding('"');
+        let value = raw.map(|s| unescape(s, escape_char, '"', escape_controls));
 
-        match name {
-            Some(name) => {
-                map.insert(name, section);
-            }
-            None if self.accepted_sections.is_none() => {
-                map.insert("root".to_string(), section);
-            }
-            _ => (),
-        }
+        let end = self.position();
 
-        if !self.errors.is_empty() {
-            None
-        } else {
-            Some(map)
+        if self.strict_strings && raw.map_or(true, |raw| start + 1 + raw.len() == self.input.len())
+        {
+            self.push_error(start, end, "Cannot finish a string");
+            return None;
         }
-    }
-
-    fn is_section_accepted(&mut self, name: &str) -> Option<bool> {
-        let sections = match &mut self.accepted_sections {
-            Some(sections) => sections,
-            None => return Some(true),
-        };
 
-        if sections.is_empty() {
+        if let Some(Err(range)) = &value {
+            self.push_error(
+                start + 1 + range.start,
+                start + 1 + range.end,
+                "Invalid unicode escape",
+            );
             return None;
         }
 
-        match sections.iter().position(|s| *s == name) {
-            Some(idx) => {
-                sections.swap_remove(idx);
-                Some(true)
+        if self.control_char_policy == ControlCharPolicy::Reject {
+            if let Some((idx, ch)) =
+                raw.and_then(|s| s.char_indices().find(|(_, ch)| ch.is_control()))
+            {
+                let lo = start + 1 + idx;
+
+                self.push_error(lo, lo + ch.len_utf8(), "Control character in a string");
+                return None;
             }
-            None => Some(false),
         }
-    }
 
-    fn slice_to_including(&mut self, ch: char) -> Option<&str> {
-        self.cur.next().map(|(start, c)| {
-            if c == ch {
-                &self.input[start..=start]
-            } else {
-                self.cur
-                    .find(|(_, c)| *c == ch)
-                    .map_or(&self.input[start..], |(end, _)| &self.input[start..=end])
-            }
-        })
+        if let Some(max) = self.max_line_length.filter(|max| end - start > *max) {
+            self.push_error(
+                start,
+                end,
+                format!("String exceeds the maximum line length of {max}"),
+            );
+            return None;
+        }
+
+        value.and_then(Result::ok)
     }
 
-    fn slice_to_excluding(&mut self, ch: char) -> Option<&str> {
-        self.cur.next().map(|(start, c)| {
-            if c == ch {
-                ""
-            } else {
-                let mut prev_element = c;
+    /// Reads a `"""`-delimited string verbatim, without processing escapes
+    /// or control characters. A line break right after the opening
+    /// delimiter is not part of the string.
+    fn multiline_string(&mut self) -> Option<Value> {
+        let start = self.position();
 
-                for (i, cur_ch) in self.cur.by_ref() {
-                    if cur_ch == ch && prev_element != '\\' {
-                        return &self.input[start..i];
-                    }
+        for _ in 0..3 {
+            self.cur.next();
+        }
 
-                    prev_element = cur_ch;
+        let content_start = start + 3;
+        let Some(len) = self.input[content_start..].find(r#"""""#) else {
+            self.push_error(start, self.input.len(), "Cannot finish a multiline string");
+            return None;
+        };
+
+        let content = self.slice(content_start..content_start + len);
+
+        while self.position() < content_start + len + 3 {
+            self.cur.next();
+        }
+
+        let content = content
+            .strip_prefix("\r\n")
+            .or_else(|| content.strip_prefix('\n'))
+            .unwrap_or(content);
+
+        Some(Value::String(content.to_owned()))
+    }
+
+    fn is_line_within_limit(&mut self, pos: usize) -> bool {
+        let max = match self.max_line_length {
+            Some(max) => max,
+            None => return true,
+        };
+
+        let lo = self.input[..pos].rfind('\n').map_or(0, |i| i + 1);
+        let hi = self.input[pos..]
+            .find('\n')
+            .map_or(self.input.len(), |i| pos + i);
+        let hi = if self.input[..hi].ends_with('\r') {
+            hi - 1
+        } else {
+            hi
+        };
+
+        if hi - lo <= max {
+            return true;
+        }
+
+        self.push_error(lo, hi, format!("Line exceeds the maximum length of {max}"));
+        false
+    }
+
+    fn trailing_data_allowed(&mut self) -> bool {
+        if !self.warn_trailing && !self.strict_trailing {
+            return true;
+        }
+
+        self.whitespace();
+
+        if matches!(self.cur.peek(), None | Some((_, '\n' | '\r'))) || self.is_at_comment() {
+            return true;
+        }
+
+        let lo = self.position();
+        let trailing = self
+            .slice_while(|ch| !matches!(ch, '\n' | '\r'))
+            .unwrap_or_default()
+            .trim_end();
+        let error = ParserError::new(
+            self.input,
+            lo,
+            lo + trailing.len(),
+            format!("Unexpected trailing data: {trailing}"),
+        );
+
+        if self.strict_trailing {
+            self.errors.push(error);
+            false
+        } else {
+            self.warnings.push(error);
+            true
+        }
+    }
+
+    fn is_at_value_end(&mut self) -> bool {
+        match self.cur.peek() {
+            Some((_, ch)) => is_value_terminator(*ch),
+            None => true,
+        }
+    }
+
+    fn keyval_sep(&mut self) -> bool {
+        self.whitespace();
+
+        let rest = &self.input[self.position()..];
+        let separator = self
+            .keyval_separators
+            .iter()
+            .find(|separator| rest.starts_with(*separator));
+
+        match separator {
+            Some(separator) => {
+                for _ in 0..separator.chars().count() {
+                    self.cur.next();
+                }
+            }
+            None => return false,
+        }
+
+        self.whitespace();
+        true
+    }
+
+    /// Reads a row, converting every cell with `convert`. Also tells whether
+    /// it is a `---` separator row.
+    fn row_with<C>(&mut self, convert: impl Fn(ValueRef<'a>) -> C) -> Option<(Vec<C>, bool)> {
+        let mut row = Vec::with_capacity(self.row_capacity);
+        let mut is_separator = true;
+
+        self.eat('|');
+
+        loop {
+            self.whitespace();
+
+            if self.is_at_comment() && self.comment().is_some() {
+                break;
+            }
+
+            if self.newline() {
+                break;
+            }
+
+            if self.cur.peek().is_none() {
+                break;
+            }
+
+            let cell = self.cell();
+
+            let value = match self.active_boolean_columns {
+                Some(idx) if self.boolean_columns[idx].1.contains(&row.len()) => {
+                    self.boolean_cell(cell)?
+                }
+                _ if self.typed_cells => typed_cell(cell),
+                _ => ValueRef::String(cell),
+            };
+
+            is_separator &= is_separator_cell(value.as_str());
+            row.push(convert(value));
+        }
+
+        self.section_rows += 1;
+        let is_separator = is_separator && !row.is_empty();
+        Some((row, is_separator))
+    }
+
+    fn boolean_cell(&mut self, cell: Cow<'a, str>) -> Option<ValueRef<'a>> {
+        match cell.as_ref() {
+            "true" => Some(ValueRef::Owned(Value::Boolean(true))),
+            "false" => Some(ValueRef::Owned(Value::Boolean(false))),
+            _ if self.strict_boolean_columns
+                && self.section_rows > 0
+                && !cell.chars().all(|c| c == '-') =>
+            {
+                self.add_error("Cannot read a boolean");
+                None
+            }
+            _ => Some(ValueRef::String(cell)),
+        }
+    }
+
+    fn enter_section(&mut self, name: &str) {
+        self.section_rows = 0;
+        self.active_boolean_columns = self.boolean_columns.iter().position(|(s, _)| *s == name);
+    }
+
+    /// Reads a trimmed cell, borrowing it from the input unless it has escapes.
+    fn cell(&mut self) -> Cow<'a, str> {
+        self.whitespace();
+
+        let start = self.position();
+        let raw = self
+            .slice_to_excluding('|')
+            .map(str::trim_end)
+            .unwrap_or_default();
+
+        unescape(raw, self.escape_char, '|', false).unwrap_or_else(|range| {
+            self.push_error(
+                start + range.start,
+                start + range.end,
+                "Invalid unicode escape",
+            );
+            Cow::Borrowed(raw)
+        })
+    }
+
+    pub fn read(&mut self) -> Option<BTreeMap<String, Section>> {
+        self.read_with(|_, _| ())
+    }
+
+    /// Like [`Parser::read`], but string entries and cells borrow from the
+    /// input unless they contain escapes, so reading them does not allocate.
+    /// Array sections are read like plain sections, and a repeated section
+    /// replaces the earlier one.
+    pub fn read_borrowed(&mut self) -> Option<BTreeMap<String, SectionRef<'a>>> {
+        let mut map = BTreeMap::new();
+        let mut section = SectionRef::default();
+        let mut name = None;
+
+        while let Some(parsed) = self.next_parsed(|cell| cell) {
+            match parsed {
+                Parsed::Element(Element::Section(n) | Element::ArraySection(n)) => {
+                    if let Some(name) = name.replace(n) {
+                        map.insert(name, mem::take(&mut section));
+                    }
+                }
+                Parsed::Element(Element::Directive(name, args)) => {
+                    self.directives.push((name, args))
+                }
+                Parsed::Element(_) => (),
+                Parsed::Entry(key, value) => {
+                    section.dictionary.insert(key, value);
+                }
+                Parsed::Row(row) => section.rows.push(row),
+            }
+        }
+
+        match name {
+            Some(name) => {
+                map.insert(name, section);
+            }
+            None if self.accepted_sections.is_none() => {
+                map.insert("root".to_owned(), section);
+            }
+            _ => (),
+        }
+
+        if self.errors.is_empty() {
+            Some(map)
+        } else {
+            None
+        }
+    }
+
+    /// Like [`Parser::read`], but returns the sections in source order. A
+    /// repeated section replaces the earlier one, as with `read`, and takes
+    /// the position of its last occurrence. An implicit `root` section comes
+    /// first.
+    pub fn read_ordered(&mut self) -> Option<Vec<(String, Section)>> {
+        let mut order = Vec::new();
+        let mut map = self.read_with(|_, element| {
+            if let Element::Section(name) = element {
+                order.push(name.clone());
+            }
+        })?;
+
+        if map.contains_key("root") && !order.iter().any(|name| name == "root") {
+            order.insert(0, "root".to_owned());
+        }
+
+        let mut sections: Vec<_> = order
+            .into_iter()
+            .rev()
+            .filter_map(|name| map.remove_entry(&name))
+            .collect();
+
+        sections.reverse();
+        Some(sections)
+    }
+
+    /// Like [`Parser::read`], but treats dots in section names as nesting:
+    /// `[server.http]` becomes the `http` dictionary entry of the `server`
+    /// section. Quoted segments such as `["weird.name"]` are not split.
+    /// A nested section cannot have rows, and a key cannot be both a plain
+    /// entry and a nested section; both are reported as errors.
+    pub fn read_nested(&mut self) -> Option<BTreeMap<String, Section>> {
+        let mut headers = Vec::new();
+        let map = self.read_with(|offset, element| {
+            if let Element::Section(name) = element {
+                headers.push((offset, name.clone()));
+            }
+        })?;
+
+        let mut nested = BTreeMap::<String, Section>::new();
+        for (name, section) in map {
+            let path = section_path(&name);
+            let (top, rest) = path.split_first().expect("a path is never empty");
+            let error = if !rest.is_empty() && !section.rows.is_empty() {
+                Some("Cannot nest a section with rows")
+            } else {
+                let parent = nested.entry(top.clone()).or_default();
+                parent.rows.extend(section.rows);
+                match nest(&mut parent.dictionary, rest, section.dictionary) {
+                    true => None,
+                    false => Some("Section conflicts with an entry"),
+                }
+            };
+
+            if let Some(desc) = error {
+                let offset = headers
+                    .iter()
+                    .rfind(|(_, header)| *header == name)
+                    .map_or(0, |(offset, _)| *offset);
+                self.push_error(offset, offset, desc);
+            }
+        }
+
+        if self.errors.is_empty() {
+            Some(nested)
+        } else {
+            None
+        }
+    }
+
+    /// Like [`Parser::read`], but always returns what could be read together
+    /// with every error. Lines with an error are skipped.
+    pub fn read_with_errors(&mut self) -> (BTreeMap<String, Section>, Vec<ParserError>) {
+        let map = self.read_partial(|_, _| ());
+        (map, self.errors.clone())
+    }
+
+    pub fn read_with_comments(mut self) -> Option<(BTreeMap<String, Section>, Comments)> {
+        let mut comments = Vec::new();
+        let map = self.read_with(|offset, element| {
+            if let Element::Comment(comment) = element {
+                comments.push((offset, comment.clone()));
+            }
+        })?;
+
+        Some((map, comments))
+    }
+
+    /// Like [`Parser::read`], but also attaches comments to elements:
+    ///
+    /// - A comment on a line of its own, with consecutive ones forming a
+    ///   block, attaches to the next section header, entry or row. Blank
+    ///   lines and directives do not end a block.
+    /// - A comment after an element on the same line attaches to that
+    ///   element, after its leading comments.
+    /// - Comments after the last element attach to [`CommentTarget::End`].
+    ///
+    /// Comments inside rows and multi-line dictionaries are not reported.
+    /// Targets are listed in source order.
+    pub fn read_with_attached_comments(
+        mut self,
+    ) -> Option<(BTreeMap<String, Section>, AttachedComments)> {
+        let input = self.input;
+        let mut attached: AttachedComments = Vec::new();
+        let mut pending = Vec::new();
+        let mut section = "root".to_owned();
+        let mut rows = 0;
+
+        let map = self.read_with(|offset, element| {
+            let target = match element {
+                Element::Comment(comment) => {
+                    let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+
+                    match attached.last_mut() {
+                        Some((_, comments)) if !input[line_start..offset].trim().is_empty() => {
+                            comments.push(comment.clone())
+                        }
+                        _ => pending.push(comment.clone()),
+                    }
+
+                    return;
+                }
+                Element::Section(name) | Element::ArraySection(name) => {
+                    section = name.clone();
+                    rows = 0;
+                    CommentTarget::Section(name.clone())
+                }
+                Element::Entry(key, _) => CommentTarget::Entry {
+                    section: section.clone(),
+                    key: key.clone(),
+                },
+                Element::Row(_) => {
+                    rows += 1;
+                    CommentTarget::Row {
+                        section: section.clone(),
+                        index: rows - 1,
+                    }
+                }
+                Element::Directive(..) => return,
+            };
+
+            attached.push((target, mem::take(&mut pending)));
+        })?;
+
+        attached.retain(|(_, comments)| !comments.is_empty());
+
+        if !pending.is_empty() {
+            attached.push((CommentTarget::End, pending));
+        }
+
+        Some((map, attached))
+    }
+
+    /// Like [`Parser::read`], but also records the blank lines before every
+    /// section header and whether the `=` of a section's entries is aligned.
+    pub fn read_with_layout(mut self) -> Option<(BTreeMap<String, Section>, Layout)> {
+        let input = self.input;
+        let mut layout = Layout::default();
+        let mut section = "root".to_owned();
+        let mut columns: BTreeMap<String, Option<usize>> = BTreeMap::new();
+
+        let map = self.read_with(|offset, element| match element {
+            Element::Section(name) | Element::ArraySection(name) => {
+                let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+                let blank_lines = input[..line_start]
+                    .split_inclusive('\n')
+                    .rev()
+                    .take_while(|line| line.trim().is_empty())
+                    .count();
+
+                layout
+                    .sections
+                    .entry(name.clone())
+                    .or_default()
+                    .blank_lines_before = blank_lines;
+                section = name.clone();
+            }
+            Element::Entry(key, _) => {
+                let line_start = input[..offset].rfind('\n').map_or(0, |i| i + 1);
+                let after_key = &input[offset + key.len()..];
+                let column = offset + key.len() - line_start + after_key.len()
+                    - after_key.trim_start_matches([' ', '\t']).len();
+
+                columns
+                    .entry(section.clone())
+                    .and_modify(|shared| *shared = shared.filter(|shared| *shared == column))
+                    .or_insert(Some(column));
+            }
+            _ => (),
+        })?;
+
+        for (name, column) in columns {
+            layout.sections.entry(name).or_default().equals_column = column;
+        }
+
+        layout.trailing_blank_lines = input
+            .split_inclusive('\n')
+            .rev()
+            .take_while(|line| line.trim().is_empty())
+            .count();
+
+        Some((map, layout))
+    }
+
+    fn read_with(
+        &mut self,
+        on_element: impl FnMut(usize, &Element),
+    ) -> Option<BTreeMap<String, Section>> {
+        let map = self.read_partial(on_element);
+
+        if !self.errors.is_empty() {
+            None
+        } else {
+            Some(map)
+        }
+    }
+
+    /// Reads all elements into sections. After an error the rest of the line
+    /// is skipped and reading continues, so every malformed line is reported.
+    fn read_partial(
+        &mut self,
+        mut on_element: impl FnMut(usize, &Element),
+    ) -> BTreeMap<String, Section> {
+        let default_capacity = self.section_capacity;
+        let row_counts = if self.two_pass {
+            Some(self.row_counts())
+        } else {
+            None
+        };
+        let section_capacity = |start: Option<usize>| match (&row_counts, start) {
+            (Some((root, _)), None) => *root,
+            (Some((_, counts)), Some(start)) => counts
+                .binary_search_by_key(&start, |&(offset, _)| offset)
+                .map_or(default_capacity, |idx| counts[idx].1),
+            (None, _) => default_capacity,
+        };
+
+        let mut map = BTreeMap::new();
+        let mut section = Section::with_capacity(section_capacity(None));
+        let mut name = None;
+
+        loop {
+            let reported = self.errors.len();
+
+            let Some(el) = self.next() else {
+                if self.errors.len() > reported && self.cur.peek().is_some() {
+                    self.skip_line();
+                    continue;
+                }
+
+                break;
+            };
+
+            on_element(self.element_start, &el);
+
+            match el {
+                Element::Section(n) => {
+                    if let Some(name) = name {
+                        self.store_section(&mut map, name, section);
+                    }
+                    name = Some((n, false));
+                    section = Section::with_capacity(section_capacity(Some(self.section_start)));
+                }
+                Element::ArraySection(n) => {
+                    if let Some(name) = name {
+                        self.store_section(&mut map, name, section);
+                    }
+                    name = Some((n, true));
+                    section = Section::with_capacity(section_capacity(Some(self.section_start)));
+                }
+                Element::Row(row) => section.rows.push(row),
+                Element::Entry(key, value) => {
+                    section.dictionary.insert(key, value);
                 }
+                Element::Directive(name, args) => self.directives.push((name, args)),
+                Element::Comment(_) => (),
+            }
+        }
+
+        match name {
+            Some(name) => self.store_section(&mut map, name, section),
+            None if self.accepted_sections.is_none() => {
+                map.insert("root".to_string(), section);
+            }
+            _ => (),
+        }
+
+        map
+    }
+
+    /// Cheaply counts the table rows before the first section header and
+    /// after every header, keyed by the header's byte offset.
+    fn row_counts(&self) -> (usize, Vec<(usize, usize)>) {
+        let mut root = 0;
+        let mut counts = Vec::new();
+        let mut offset = 0;
+
+        for line in self.input.split_inclusive('\n') {
+            let trimmed = line.trim_start_matches([' ', '\t']);
+
+            match (trimmed.as_bytes().first(), counts.last_mut()) {
+                (Some(b'['), _) => counts.push((offset + line.len() - trimmed.len(), 0)),
+                (Some(b'|'), Some((_, rows))) => *rows += 1,
+                (Some(b'|'), None) => root += 1,
+                _ => (),
+            }
+
+            offset += line.len();
+        }
+
+        (root, counts)
+    }
+
+    fn store_section(
+        &mut self,
+        map: &mut BTreeMap<String, Section>,
+        (name, is_array): (String, bool),
+        section: Section,
+    ) {
+        if is_array {
+            self.section_arrays.entry(name).or_default().push(section);
+        } else if let (true, Some(existing)) = (self.merge_sections, map.get_mut(&name)) {
+            existing.dictionary.extend(section.dictionary);
+            existing.rows.extend(section.rows);
+        } else {
+            map.insert(name, section);
+        }
+    }
+
+    fn is_section_accepted(&mut self, name: &str) -> Option<bool> {
+        let sections = match &mut self.accepted_sections {
+            Some(sections) => sections,
+            None => return Some(true),
+        };
+
+        if sections.is_empty() {
+            return None;
+        }
+
+        let eq = |a: &str, b: &str| match self.case_insensitive_sections {
+            true => a.eq_ignore_ascii_case(b),
+            false => a == b,
+        };
+
+        match sections.iter().position(|s| eq(s, name)) {
+            Some(idx) => {
+                sections.swap_remove(idx);
+                Some(true)
+            }
+            None => Some(sections.iter().any(|s| {
+                s.strip_suffix('*').map_or(false, |prefix| {
+                    name.get(..prefix.len())
+                        .map_or(false, |head| eq(head, prefix))
+                })
+            })),
+        }
+    }
+
+    fn slice_to_including(&mut self, ch: char) -> Option<&'a str> {
+        let (start, c) = self.cur.next()?;
+        let end = if c == ch {
+            start + ch.len_utf8()
+        } else {
+            self.cur
+                .find(|(_, c)| *c == ch)
+                .map_or(self.input.len(), |(end, _)| end + ch.len_utf8())
+        };
+
+        Some(self.slice(start..end))
+    }
+
+    fn slice_to_excluding(&mut self, ch: char) -> Option<&'a str> {
+        let escape_char = self.escape_char;
+        let (start, c) = self.cur.next()?;
+
+        if c == ch {
+            return Some("");
+        }
+
+        let mut escaped = c == escape_char;
+        let mut end = self.input.len();
+
+        for (i, cur_ch) in self.cur.by_ref() {
+            if cur_ch == ch && !escaped {
+                end = i;
+                break;
+            }
+
+            escaped = !escaped && cur_ch == escape_char;
+        }
+
+        Some(self.slice(start..end))
+    }
+
+    fn slice_while(&mut self, predicate: impl Fn(char) -> bool) -> Option<&'a str> {
+        let &(start, c) = self.cur.peek()?;
+
+        if !predicate(c) {
+            return None;
+        }
+
+        self.cur.next();
+
+        while let Some(&(_, c)) = self.cur.peek() {
+            if !predicate(c) {
+                break;
+            }
+
+            self.cur.next();
+        }
+
+        let end = self.position();
+        Some(self.slice(start..end))
+    }
+
+    /// Slices the input. With boundary checks on, a range that doesn't lie on
+    /// char boundaries panics in debug builds and is reported as an error
+    /// (yielding an empty slice) in release builds.
+    fn slice(&mut self, range: Range<usize>) -> &'a str {
+        let input = self.input;
+
+        if self.boundary_checks && input.get(range.clone()).is_none() {
+            if cfg!(debug_assertions) {
+                panic!("slice {range:?} is not on a char boundary of the input");
+            }
+
+            self.push_error(range.start, range.end, "Slice is not on a char boundary");
+            return "";
+        }
+
+        &input[range]
+    }
+
+    fn position(&mut self) -> usize {
+        self.cur.peek().map_or(self.input.len(), |(pos, _)| *pos)
+    }
+
+    fn add_error(&mut self, message: &str) {
+        let mut it = self.cur.clone();
+        let lo = it.next().map(|p| p.0).unwrap_or(self.input.len());
+        let hi = it.next().map(|p| p.0).unwrap_or(self.input.len());
+
+        self.push_error(lo, hi, message);
+    }
+
+    fn push_error(&mut self, lo: usize, hi: usize, desc: impl Into<String>) {
+        self.errors.push(ParserError::new(self.input, lo, hi, desc));
+    }
+}
+
+/// Reads the whole of `reader` into memory and parses it with
+/// [`Parser::read`]. The input is buffered as one `String`, so memory use is
+/// the size of the document plus the parsed sections. Parse errors are
+/// returned as [`io::ErrorKind::InvalidData`] wrapping
+/// [`IonError::ParserErrors`].
+pub fn read_from(mut reader: impl io::Read) -> io::Result<BTreeMap<String, Section>> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+
+    let mut parser = Parser::new(&input);
+    parser.read().ok_or_else(|| {
+        let errors = mem::take(&mut parser.errors);
+        io::Error::new(io::ErrorKind::InvalidData, IonError::ParserErrors(errors))
+    })
+}
+
+/// Returns the source text of an element span yielded by [`Parser::spanned`],
+/// or `None` when `span` is out of bounds or not on char boundaries of
+/// `input`, e.g. because it was yielded for another input.
+pub fn element_source(input: &str, span: Range<usize>) -> Option<&str> {
+    input.get(span)
+}
+
+/// Splits a section name on dots outside of double quotes.
+fn section_path(name: &str) -> Vec<String> {
+    let mut segments = vec![String::new()];
+    let mut quoted = false;
+
+    for c in name.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            '.' if !quoted => segments.push(String::new()),
+            c => segments.last_mut().unwrap().push(c),
+        }
+    }
+
+    segments.iter().map(|s| s.trim().to_owned()).collect()
+}
+
+/// Stores `entries` in the dictionary found by following `path` from
+/// `dictionary`, creating the missing levels. Returns false when a key on the
+/// way is already taken by something other than a dictionary.
+fn nest(dictionary: &mut Dictionary, path: &[String], entries: Dictionary) -> bool {
+    if let Some((key, rest)) = path.split_first() {
+        let level = dictionary
+            .entry(key.clone())
+            .or_insert_with(|| Value::Dictionary(Dictionary::new()));
+
+        return match level {
+            Value::Dictionary(inner) => nest(inner, rest, entries),
+            _ => false,
+        };
+    }
+
+    entries
+        .into_iter()
+        .all(|(key, value)| match (dictionary.entry(key), value) {
+            (btree_map::Entry::Vacant(entry), value) => {
+                entry.insert(value);
+                true
+            }
+            (btree_map::Entry::Occupied(mut entry), Value::Dictionary(inner)) => {
+                match entry.get_mut() {
+                    Value::Dictionary(existing) => nest(existing, &[], inner),
+                    _ => false,
+                }
+            }
+            (btree_map::Entry::Occupied(_), _) => false,
+        })
+}
+
+/// Whether every cell of a non-empty `row` looks like `---`, `:--`, `--:` or
+/// `:-:`.
+fn is_separator_cell(cell: Option<&str>) -> bool {
+    let dashes = cell.map(|s| {
+        let s = s.strip_prefix(':').unwrap_or(s);
+        s.strip_suffix(':').unwrap_or(s)
+    });
+
+    dashes.map_or(false, |d| !d.is_empty() && d.bytes().all(|b| b == b'-'))
+}
+
+fn typed_cell(cell: Cow<str>) -> ValueRef {
+    if !matches!(cell.chars().next(), Some('0'..='9' | 't' | 'f')) {
+        return ValueRef::String(cell);
+    }
+
+    match parse_value_at(&cell, 0..cell.len()) {
+        Ok(value @ (Value::Integer(_) | Value::Float(_) | Value::Boolean(_))) => {
+            ValueRef::Owned(value)
+        }
+        _ => ValueRef::String(cell),
+    }
+}
+
+pub(crate) fn parse_value_at(input: &str, span: Range<usize>) -> Result<Value, ParserError> {
+    let Some(raw) = input.get(span.clone()) else {
+        return Err(ParserError::new(
+            input,
+            span.start,
+            span.end,
+            "Span is outside of the input",
+        ));
+    };
+    let mut parser = Parser::new(raw);
+    let value = parser.value();
+    parser.whitespace();
+
+    if parser.cur.peek().is_some() && parser.errors.is_empty() {
+        parser.add_error("Unexpected trailing data");
+    }
+
+    match (value, parser.errors.into_iter().next()) {
+        (Some(value), None) => Ok(value),
+        (_, error) => {
+            let (lo, hi, desc) = match error {
+                Some(error) => (error.lo, error.hi, error.desc),
+                None => (span.len(), span.len(), "Cannot read a value".to_owned()),
+            };
+
+            Err(ParserError::new(
+                input,
+                span.start + lo,
+                span.start + hi,
+                desc,
+            ))
+        }
+    }
+}
+
+fn is_value_terminator(ch: char) -> bool {
+    matches!(ch, ' ' | '\t' | '\n' | '\r' | ',' | ']' | '}' | '#')
+}
+
+/// Replaces escape sequences in `s`, borrowing it when there is nothing to
+/// replace. On an invalid `\u{...}` escape returns its range in `s`.
+fn unescape(
+    s: &str,
+    escape_char: char,
+    delimiter: char,
+    escape_controls: bool,
+) -> Result<Cow<'_, str>, Range<usize>> {
+    let needs_escaping = escape_controls && s.contains(char::is_control);
+
+    if !needs_escaping && !s.contains(escape_char) {
+        return Ok(Cow::Borrowed(s));
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.char_indices();
+
+    while let Some((idx, c)) = chars.next() {
+        if escape_controls && c.is_control() {
+            out.extend(c.escape_default());
+            continue;
+        }
+
+        if c != escape_char {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next().map(|(_, next)| next) {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('u') if s[idx + c.len_utf8() + 1..].starts_with('{') => {
+                let digits_start = idx + c.len_utf8() + 2;
+                let digits_end = s[digits_start..].find('}').map(|i| digits_start + i);
+
+                let ch = digits_end
+                    .map(|end| &s[digits_start..end])
+                    .filter(|digits| {
+                        (1..=6).contains(&digits.len())
+                            && digits.chars().all(|ch| ch.is_ascii_hexdigit())
+                    })
+                    .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+                    .and_then(char::from_u32);
+
+                match (ch, digits_end) {
+                    (Some(ch), Some(end)) => {
+                        out.push(ch);
+                        chars.by_ref().take_while(|(i, _)| *i < end).for_each(drop);
+                    }
+                    _ => return Err(idx..digits_end.map_or(s.len(), |end| end + 1)),
+                }
+            }
+            Some(next) if next == delimiter || next == escape_char => out.push(next),
+            Some(next) => {
+                out.push(c);
+                out.push(next);
+            }
+            None => out.push(c),
+        }
+    }
+
+    Ok(Cow::Owned(out))
+}
+
+#[derive(Clone, Debug)]
+pub struct ParserError {
+    pub lo: usize,
+    pub hi: usize,
+    /// 1-based line of `lo`.
+    pub line: usize,
+    /// 1-based column of `lo`, counted in chars.
+    pub col: usize,
+    pub desc: String,
+}
+
+impl ParserError {
+    pub(crate) fn new(input: &str, lo: usize, hi: usize, desc: impl Into<String>) -> Self {
+        let before = input.get(..lo).unwrap_or(input);
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+
+        Self {
+            lo,
+            hi,
+            line: before.matches('\n').count() + 1,
+            col: before[line_start..].chars().count() + 1,
+            desc: desc.into(),
+        }
+    }
+
+    /// Renders the error like a compiler diagnostic: the lines spanned by
+    /// `lo..hi` with one line of context around them and `^` markers under
+    /// the offending text. Tabs are expanded to four spaces.
+    pub fn render(&self, input: &str) -> String {
+        let lines = source_lines(input);
+        let lo = self.lo.min(input.len());
+        let hi = self.hi.clamp(lo + 1, input.len().max(lo + 1));
+        let (first, last) = (line_of(&lines, lo), line_of(&lines, hi - 1));
+        let (from, to) = (first.saturating_sub(1), (last + 1).min(lines.len() - 1));
+
+        let width = (to + 1).to_string().len();
+        let pad = " ".repeat(width);
+        let col = input[lines[first].0..lo].chars().count() + 1;
+
+        let mut out = format!(
+            "error: {}\n{pad}--> {}:{col}\n{pad} |\n",
+            self.desc,
+            first + 1
+        );
+
+        for (idx, &(start, line)) in lines.iter().enumerate().take(to + 1).skip(from) {
+            let _ = writeln!(out, "{:>width$} | {}", idx + 1, expand_tabs(line));
+
+            if (first..=last).contains(&idx) {
+                let from = if idx == first { lo - start } else { 0 };
+                let to = if idx == last {
+                    (hi - start).min(line.len())
+                } else {
+                    line.len()
+                };
+                let _ = writeln!(out, "{pad} | {}", underline(line, from, to));
+            }
+        }
+
+        out
+    }
+
+    /// Renders the line containing `lo` followed by `^` markers under
+    /// `lo..hi` (cut at the line end) and the description, e.g.
+    ///
+    /// ```text
+    /// b = ?
+    ///     ^ Cannot read a value
+    /// ```
+    pub fn snippet(&self, input: &str) -> String {
+        let lines = source_lines(input);
+        let lo = self.lo.min(input.len());
+        let (start, line) = lines[line_of(&lines, lo)];
+
+        format!(
+            "{}\n{} {}",
+            expand_tabs(line),
+            underline(line, lo - start, self.hi.max(lo) - start),
+            self.desc
+        )
+    }
+}
+
+/// Splits `input` into lines without their `\r\n` or `\n` endings, together
+/// with the offset each line starts at.
+fn source_lines(input: &str) -> Vec<(usize, &str)> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+
+    for line in input.split('\n') {
+        lines.push((start, line.strip_suffix('\r').unwrap_or(line)));
+        start += line.len() + 1;
+    }
+
+    lines
+}
+
+fn line_of(lines: &[(usize, &str)], pos: usize) -> usize {
+    lines.partition_point(|(start, _)| *start <= pos) - 1
+}
+
+fn expand_tabs(line: &str) -> String {
+    line.replace('\t', "    ")
+}
+
+/// Returns `^` markers under `from..to` of `line`, at least one and cut at
+/// the line end, indented to line up with [`expand_tabs`] output.
+fn underline(line: &str, from: usize, to: usize) -> String {
+    let from = from.min(line.len());
+    let to = to.clamp(from, line.len());
+
+    format!(
+        "{}{}",
+        " ".repeat(display_width(&line[..from])),
+        "^".repeat(display_width(&line[from..to]).max(1))
+    )
+}
+
+fn display_width(s: &str) -> usize {
+    s.chars().map(|c| if c == '\t' { 4 } else { 1 }).sum()
+}
+
+impl error::Error for ParserError {
+    fn description(&self) -> &str {
+        "error parsing Ion"
+    }
+}
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "error at {}:{}: {}", self.line, self.col, self.desc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Element::{self, Comment, Entry, Row};
+    use crate::{
+        element_source, read_from, CommentTarget, ControlCharPolicy, Dictionary, EmptyValue,
+        IdentRules, IonError, Parser, ParserError, Section, Value, ValueRef,
+    };
+    use std::collections::BTreeMap;
+    use std::io;
+
+    #[test]
+    fn finish_string() {
+        let mut p = Parser::new("\"foObar\"");
+        assert_eq!(Some("foObar"), p.finish_string().unwrap().as_str());
+
+        let mut p = Parser::new("\"foObar");
+        assert_eq!(Some("foObar"), p.finish_string().unwrap().as_str());
+
+        let mut p = Parser::new("\"\"");
+        assert_eq!(Some(""), p.finish_string().unwrap().as_str());
+
+        let mut p = Parser::new("");
+        assert_eq!(None, p.finish_string());
+    }
+
+    #[test]
+    fn unescape() {
+        use std::borrow::Cow;
+
+        assert!(matches!(
+            super::unescape("plain ż", '\\', '"', false),
+            Ok(Cow::Borrowed("plain ż"))
+        ));
+        assert!(matches!(
+            super::unescape("a\tb", '\\', '"', false),
+            Ok(Cow::Borrowed("a\tb"))
+        ));
+        assert_eq!(
+            Ok("a\\tb".to_owned()),
+            super::unescape("a\tb", '\\', '"', true).map(Cow::into_owned)
+        );
+        assert!(matches!(
+            super::unescape("a\\\"b\\n\\u{17C}", '\\', '"', false),
+            Ok(Cow::Owned(s)) if s == "a\"b\nż"
+        ));
+
+        let raw = r#"a = "no escapes" b = "with \"escapes\"\t" "#;
+        let mut p = Parser::new(raw);
+        assert_eq!(
+            Some(Entry("a".to_owned(), Value::new_string("no escapes"))),
+            p.next()
+        );
+        assert_eq!(
+            Some(Entry(
+                "b".to_owned(),
+                Value::new_string("with \"escapes\"\t")
+            )),
+            p.next()
+        );
+    }
+
+    #[test]
+    fn strict_strings() {
+        for raw in ["\"foObar\"", "\"\"", "\"a\\\"b\" "] {
+            let mut p = Parser::new(raw).with_strict_strings(true);
+            assert!(p.finish_string().is_some(), "{raw}");
+        }
+
+        for raw in ["\"foObar", "\"", "\"a\\\""] {
+            let mut p = Parser::new(raw).with_strict_strings(true);
+            assert_eq!(None, p.finish_string(), "{raw}");
+            assert_eq!("Cannot finish a string", p.errors[0].desc);
+            assert_eq!((0, raw.len()), (p.errors[0].lo, p.errors[0].hi));
+        }
+
+        let mut p = Parser::new("a = \"x\"\nb = \"truncated").with_strict_strings(true);
+        assert_eq!(None, p.read());
+        assert_eq!(12, p.errors[0].lo);
+
+        let mut p = Parser::new("a = \"x\"\nb = \"truncated");
+        assert!(p.read().is_some());
+    }
+
+    #[test]
+    fn finish_string_with_escapes() {
+        let mut p = Parser::new(r#""a\\b\nc\"d\\""#);
+        assert_eq!(Some("a\\b\nc\"d\\"), p.finish_string().unwrap().as_str());
+
+        let mut p = Parser::new(r#""a\\nb""#);
+        assert_eq!(Some("a\\nb"), p.finish_string().unwrap().as_str());
+
+        let mut p = Parser::new(r#""a\tb\rc\\t\\\td""#);
+        assert_eq!(Some("a\tb\rc\\t\\\td"), p.finish_string().unwrap().as_str());
+    }
+
+    #[test]
+    fn multiline_string() {
+        let raw = "a = \"\"\"\nfirst \"line\"\n\n  third \\n line\n\"\"\"\nb = \"\"\"inline\"\"\"\nc = 1\n";
+        let section = Parser::new(raw).read().unwrap().remove("root").unwrap();
+        assert_eq!(
+            Some("first \"line\"\n\n  third \\n line\n"),
+            section.get("a").unwrap().as_str()
+        );
+        assert_eq!(Some("inline"), section.get("b").unwrap().as_str());
+
+        let reparsed = Parser::new(&section.to_string())
+            .read()
+            .unwrap()
+            .remove("root")
+            .unwrap();
+        assert_eq!(section, reparsed);
+
+        let mut p = Parser::new("\"\"\"\r\nx\r\n\"\"\"");
+        assert_eq!(Some(Value::new_string("x\r\n")), p.value());
+
+        let mut p = Parser::new("\"\"\"\"\"\"");
+        assert_eq!(Some(Value::new_string("")), p.value());
+
+        let mut p = Parser::new("a = 1\nb = \"\"\"\nopen \"\"\n");
+        assert_eq!(None, p.read());
+        assert_eq!("Cannot finish a multiline string", p.errors[0].desc);
+        assert_eq!((10, 22), (p.errors[0].lo, p.errors[0].hi));
+
+        let mut p = Parser::new("a = \"\"\"x\ny\"\"\"\n").with_lazy_values(true);
+        let section = p.read().unwrap().remove("root").unwrap();
+        assert_eq!(Some(&Value::Lazy(4..13)), section.get("a"));
+    }
+
+    #[test]
+    fn unicode_escapes() {
+        let mut p = Parser::new(r#""caf\u{e9} \u{1F600}\u{41}\u{0041}!""#);
+        assert_eq!(Some("café 😀AA!"), p.finish_string().unwrap().as_str());
+
+        let mut p = Parser::new(r#""\\u{41} \u41""#);
+        assert_eq!(Some("\\u{41} \\u41"), p.finish_string().unwrap().as_str());
+
+        for (raw, lo, hi) in [
+            (r#""a\u{110000}""#, 2, 12),
+            (r#""\u{D800}""#, 1, 9),
+            (r#""\u{}""#, 1, 5),
+            (r#""\u{xyz}""#, 1, 8),
+            (r#""\u{1234567}""#, 1, 12),
+            (r#""\u{41""#, 1, 6),
+            (r#""\u{+41}""#, 1, 8),
+        ] {
+            let mut p = Parser::new(raw);
+            assert_eq!(None, p.finish_string(), "{raw}");
+            assert_eq!("Invalid unicode escape", p.errors[0].desc);
+            assert_eq!((lo, hi), (p.errors[0].lo, p.errors[0].hi), "{raw}");
+        }
+
+        let mut p = Parser::new(r#"| caf\u{e9} | \u{zz} |"#);
+        assert_eq!(
+            Some(Row(vec![
+                Value::new_string("café"),
+                Value::new_string("\\u{zz}")
+            ])),
+            p.next()
+        );
+        assert_eq!((14, 20), (p.errors[0].lo, p.errors[0].hi));
+    }
+
+    #[test]
+    fn tab_escapes_round_trip() {
+        let raw = "a = \"x\\ty\\r\tz\"\n";
+        let section = Parser::new(raw).read().unwrap().remove("root").unwrap();
+        assert_eq!(Some("x\ty\r\tz"), section.get("a").unwrap().as_str());
+
+        let displayed = section.to_string();
+        assert_eq!("a = \"x\\ty\\r\\tz\"\n", displayed);
+
+        let reparsed = Parser::new(&displayed)
+            .read()
+            .unwrap()
+            .remove("root")
+            .unwrap();
+        assert_eq!(section, reparsed);
+    }
+
+    #[test]
+    fn control_char_policy() {
+        let raw = "\"a\tb\0c\"";
+
+        let mut p = Parser::new(raw);
+        assert_eq!(Some("a\tb\0c"), p.finish_string().unwrap().as_str());
+
+        let mut p = Parser::new(raw).with_control_char_policy(ControlCharPolicy::Allow);
+        assert_eq!(Some("a\tb\0c"), p.finish_string().unwrap().as_str());
+
+        let mut p = Parser::new(raw).with_control_char_policy(ControlCharPolicy::Escape);
+        assert_eq!(Some("a\\tb\\u{0}c"), p.finish_string().unwrap().as_str());
+
+        let mut p = Parser::new(raw).with_control_char_policy(ControlCharPolicy::Reject);
+        assert_eq!(None, p.finish_string());
+        assert_eq!("Control character in a string", p.errors[0].desc);
+        assert_eq!((2, 3), (p.errors[0].lo, p.errors[0].hi));
+
+        let mut p = Parser::new("\"a\0\"").with_control_char_policy(ControlCharPolicy::Reject);
+        assert_eq!(None, p.finish_string());
+        assert_eq!((2, 3), (p.errors[0].lo, p.errors[0].hi));
+
+        let mut p = Parser::new(r#""a\"b""#).with_control_char_policy(ControlCharPolicy::Reject);
+        assert_eq!(Some("a\"b"), p.finish_string().unwrap().as_str());
+    }
+
+    #[test]
+    fn escape_char() {
+        let mut p = Parser::new(r#""C:\dir\`"q`"`n``""#).with_escape_char('`');
+        assert_eq!(
+            Some("C:\\dir\\\"q\"\n`"),
+            p.finish_string().unwrap().as_str()
+        );
+
+        let mut p = Parser::new(r#"| a`|b | C:\x\ | c\n |"#).with_escape_char('`');
+        assert_eq!(
+            Some(Row(vec![
+                Value::new_string("a|b"),
+                Value::new_string("C:\\x\\"),
+                Value::new_string("c\\n"),
+            ])),
+            p.next()
+        );
+    }
+
+    #[test]
+    fn finish_array() {
+        let mut p = Parser::new("[\"a\"");
+        assert_eq!(None, p.finish_array());
+
+        let mut p = Parser::new("[");
+        assert_eq!(None, p.finish_array());
+
+        let mut p = Parser::new("[]");
+        assert_eq!(Some(Value::Array(vec![])), p.finish_array());
+
+        let mut p = Parser::new("[\"a\"]");
+        assert_eq!(Some(Value::new_string_array("a")), p.finish_array());
+        let mut p = Parser::new("[1, 2,3 ,\n4]");
+        assert_eq!("[ 1, 2, 3, 4 ]", p.finish_array().unwrap().to_string());
+
+        let mut p = Parser::new("[1 2]");
+        assert_eq!(None, p.finish_array());
+        assert_eq!("Expected ',' or ']'", p.errors[0].desc);
+        assert_eq!(3, p.errors[0].lo);
+
+        let mut p = Parser::new("[[1] \"a\"]");
+        assert_eq!(None, p.finish_array());
+        assert_eq!("Expected ',' or ']'", p.errors[0].desc);
+
+        let mut p = Parser::new("[1 2, \"a\"]").with_whitespace_separated_arrays(true);
+        assert_eq!("[ 1, 2, \"a\" ]", p.finish_array().unwrap().to_string());
+    }
+
+    #[test]
+    fn finish_dictionary() {
+        let mut p = Parser::new("{");
+        assert_eq!(None, p.finish_dictionary());
+
+        let mut p = Parser::new("{ foo");
+        assert_eq!(None, p.finish_dictionary());
+
+        let mut p = Parser::new("{ foo = ");
+        assert_eq!(None, p.finish_dictionary());
+
+        let mut p = Parser::new("{ foo = \"bar\"");
+        assert_eq!(None, p.finish_dictionary());
+
+        let mut p = Parser::new("{ foo = [\"bar\"");
+        assert_eq!(None, p.finish_dictionary());
+
+        let mut p = Parser::new("{ foo = [\"bar\"]");
+        assert_eq!(None, p.finish_dictionary());
+
+        let mut p = Parser::new("{}");
+        assert_eq!(
+            Some(Value::Dictionary(Dictionary::new())),
+            p.finish_dictionary()
+        );
+
+        let mut p = Parser::new("{ foo = [\"bar\"] }");
+        assert_eq!(
+            "{ foo = [ \"bar\" ] }",
+            p.finish_dictionary().map(|d| d.to_string()).unwrap()
+        );
+    }
+
+    #[test]
+    fn finish_dictionary_with_comments() {
+        let mut p = Parser::new("{ a = 1 # note\n b = 2 }");
+        let mut expected = Dictionary::new();
+        expected.insert("a".to_owned(), Value::Integer(1));
+        expected.insert("b".to_owned(), Value::Integer(2));
+        assert_eq!(Some(Value::Dictionary(expected)), p.finish_dictionary());
+
+        let raw = r#"{
+            # leading
+            a = "x # y" # trailing
+            # between
+            b = [1, 2] # after array
+        }"#;
+        let mut p = Parser::new(raw);
+        let mut expected = Dictionary::new();
+        expected.insert("a".to_owned(), Value::String("x # y".to_owned()));
+        expected.insert(
+            "b".to_owned(),
+            Value::Array(vec![Value::Integer(1), Value::Integer(2)]),
+        );
+        assert_eq!(Some(Value::Dictionary(expected)), p.finish_dictionary());
+        assert!(p.errors.is_empty());
+    }
+
+    #[test]
+    fn bytes() {
+        let mut p = Parser::new("data = b64\"SGVsbG8=\"").with_base64(true);
+        let section = p.read().unwrap().remove("root").unwrap();
+        let value = section.get("data").unwrap();
+        assert_eq!(Some(&b"Hello"[..]), value.as_bytes());
+        assert_eq!("b64\"SGVsbG8=\"", value.to_string());
+        assert_eq!("data = b64\"SGVsbG8=\"\n", section.to_string());
+
+        let mut p = Parser::new("data = b64\"SGVsbG8\"").with_base64(true);
+        assert_eq!(None, p.read());
+        assert_eq!("Cannot decode base64", p.errors[0].desc);
+
+        let mut p = Parser::new("data = b64\"SGVsbG8=\"");
+        assert_eq!(None, p.read());
+        assert_eq!("Cannot read a value", p.errors[0].desc);
+    }
+
+    #[test]
+    fn number() {
+        let mut p = Parser::new("12");
+        assert_eq!(Some(Value::Integer(12)), p.number());
+
+        let mut p = Parser::new("12abc");
+        assert_eq!(None, p.number());
+        assert_eq!(1, p.errors.len());
+
+        let mut p = Parser::new("12.3.4");
+        assert_eq!(None, p.number());
+        assert_eq!(1, p.errors.len());
+    }
+
+    #[test]
+    fn hex_number() {
+        let mut p = Parser::new("0x0");
+        assert_eq!(Some(Value::Integer(0)), p.number());
+
+        let mut p = Parser::new("0xff, 1");
+        assert_eq!(Some(Value::Integer(255)), p.number());
+        assert_eq!(Some((4, ',')), p.cur.next());
+
+        let mut p = Parser::new("0xDEADBEEF");
+        assert_eq!(Some(Value::Integer(0xDEAD_BEEF)), p.number());
+
+        let mut p = Parser::new("0XFf00");
+        assert_eq!(Some(Value::Integer(0xFF00)), p.number());
+
+        let mut p = Parser::new("0x");
+        assert_eq!(None, p.number());
+        assert_eq!("Cannot read a hexadecimal number", p.errors[0].desc);
+
+        let mut p = Parser::new("0xG");
+        assert_eq!(None, p.number());
+        assert_eq!("Cannot read a hexadecimal number", p.errors[0].desc);
+        assert_eq!(Some((2, 'G')), p.cur.next());
+
+        let mut p = Parser::new("0x1FFFFFFFFFFFFFFFF");
+        assert_eq!(None, p.number());
+        assert_eq!(1, p.errors.len());
+
+        let mut p = Parser::new("mask = 0xFF00\nn = 0\n");
+        let section = p.read().unwrap().remove("root").unwrap();
+        assert_eq!(Some(&Value::Integer(0xFF00)), section.get("mask"));
+        assert_eq!(Some(&Value::Integer(0)), section.get("n"));
+    }
+
+    #[test]
+    fn octal_and_binary_number() {
+        let mut p = Parser::new("0o755");
+        assert_eq!(Some(Value::Integer(0o755)), p.number());
+
+        let mut p = Parser::new("0O17");
+        assert_eq!(Some(Value::Integer(0o17)), p.number());
+
+        let mut p = Parser::new("0b1010");
+        assert_eq!(Some(Value::Integer(0b1010)), p.number());
+
+        let mut p = Parser::new("0B1]");
+        assert_eq!(Some(Value::Integer(1)), p.number());
+
+        let mut p = Parser::new("0");
+        assert_eq!(Some(Value::Integer(0)), p.number());
+
+        let mut p = Parser::new("0o8");
+        assert_eq!(None, p.number());
+        assert_eq!("Cannot read an octal number", p.errors[0].desc);
+        assert_eq!((2, 3), (p.errors[0].lo, p.errors[0].hi));
+
+        let mut p = Parser::new("0b102");
+        assert_eq!(None, p.number());
+        assert_eq!("Cannot read a binary number", p.errors[0].desc);
+        assert_eq!((4, 5), (p.errors[0].lo, p.errors[0].hi));
+
+        let mut p = Parser::new("0b");
+        assert_eq!(None, p.number());
+        assert_eq!((2, 2), (p.errors[0].lo, p.errors[0].hi));
+
+        let mut p = Parser::new("mode = 0o755\nflags = [0b1, 0]\n");
+        let section = p.read().unwrap().remove("root").unwrap();
+        assert_eq!(Some(&Value::Integer(0o755)), section.get("mode"));
+        assert_eq!(
+            Some(&Value::Array(vec![Value::Integer(1), Value::Integer(0)])),
+            section.get("flags")
+        );
+    }
+
+    #[test]
+    fn underscore_number() {
+        let mut p = Parser::new("1_000");
+        assert_eq!(Some(Value::Integer(1000)), p.number());
+
+        let mut p = Parser::new("3_000.5");
+        assert_eq!(Some(Value::Float(3000.5)), p.number());
+
+        let mut p = Parser::new("1_0.0_1e1_0");
+        assert_eq!(Some(Value::Float(10.01e10)), p.number());
+
+        let mut p = Parser::new("100_");
+        assert_eq!(None, p.number());
+        assert_eq!("Invalid digit separator", p.errors[0].desc);
+
+        let mut p = Parser::new("1__0");
+        assert_eq!(None, p.number());
+        assert_eq!("Invalid digit separator", p.errors[0].desc);
+
+        let mut p = Parser::new("1._5");
+        assert_eq!(None, p.number());
+        assert_eq!("Invalid digit separator", p.errors[0].desc);
+
+        let mut p = Parser::new("_100");
+        assert_eq!(None, p.value());
+        assert_eq!("Cannot read a value", p.errors[0].desc);
+
+        let mut p = Parser::new("population = 1_000_000\n");
+        let section = p.read().unwrap().remove("root").unwrap();
+        assert_eq!(Some(&Value::Integer(1_000_000)), section.get("population"));
+    }
+
+    #[test]
+    fn exponent_number() {
+        let mut p = Parser::new("1e10");
+        assert_eq!(Some(Value::Float(1e10)), p.number());
+
+        let mut p = Parser::new("1.5e-3");
+        assert_eq!(Some(Value::Float(1.5e-3)), p.number());
+
+        let mut p = Parser::new("2E+4, 1");
+        assert_eq!(Some(Value::Float(2e4)), p.number());
+        assert_eq!(Some((4, ',')), p.cur.next());
+
+        let mut p = Parser::new("1e");
+        assert_eq!(None, p.number());
+        assert_eq!("Cannot read an exponent", p.errors[0].desc);
+
+        let mut p = Parser::new("1e+ ");
+        assert_eq!(None, p.number());
+        assert_eq!("Cannot read an exponent", p.errors[0].desc);
+
+        let mut p = Parser::new("a = 1.2e9\nb = [3E-4, 5]\n");
+        let section = p.read().unwrap().remove("root").unwrap();
+        assert_eq!(Some(&Value::Float(1.2e9)), section.get("a"));
+        assert_eq!(
+            Some(&Value::Array(vec![Value::Float(3e-4), Value::Integer(5)])),
+            section.get("b")
+        );
+    }
+
+    #[test]
+    fn lenient_number() {
+        let mut p = Parser::new("12").with_lenient_numbers(true);
+        assert_eq!(Some(Value::Integer(12)), p.number());
+
+        let mut p = Parser::new("12abc, 1").with_lenient_numbers(true);
+        assert_eq!(Some(Value::String("12abc".to_owned())), p.number());
+        assert_eq!(Some((5, ',')), p.cur.next());
+
+        let mut p = Parser::new("12.3.4").with_lenient_numbers(true);
+        assert_eq!(Some(Value::String("12.3.4".to_owned())), p.number());
+        assert!(p.errors.is_empty());
+
+        let mut p = Parser::new("x = 12abc\ny = 1").with_lenient_numbers(true);
+        let section = p.read().unwrap().remove("root").unwrap();
+        assert_eq!(Some(&Value::String("12abc".to_owned())), section.get("x"));
+        assert_eq!(Some(&Value::Integer(1)), section.get("y"));
+    }
+
+    #[test]
+    fn space_grouped_numbers() {
+        let raw = "n = 1 000 000\nm = 12 345 # comment\nk = 1 000\n";
+        let section = Parser::new(raw)
+            .with_space_grouped_numbers(true)
+            .read()
+            .unwrap()
+            .remove("root")
+            .unwrap();
+
+        assert_eq!(Some(&Value::Integer(1_000_000)), section.get("n"));
+        assert_eq!(Some(&Value::Integer(12_345)), section.get("m"));
+        assert_eq!(Some(&Value::Integer(1_000)), section.get("k"));
+
+        let mut p = Parser::new("n = 1 000 000").with_strict_trailing(true);
+        assert!(p.read().is_none());
+
+        let mut p = Parser::new("n = 1 00")
+            .with_space_grouped_numbers(true)
+            .with_strict_trailing(true);
+        assert!(p.read().is_none());
+    }
+
+    #[test]
+    fn space_grouped_numbers_outside_entries() {
+        let raw = "[A]\narr = [1, 000]\n[T]\n| 1 000 |\n";
+        let map = Parser::new(raw)
+            .with_space_grouped_numbers(true)
+            .read()
+            .unwrap();
+
+        assert_eq!(
+            Some(&Value::Array(vec![Value::Integer(1), Value::Integer(0)])),
+            map["A"].get("arr")
+        );
+        assert_eq!(vec![vec![Value::String("1 000".to_owned())]], map["T"].rows);
+    }
+
+    #[test]
+    fn slice_to_inc() {
+        let mut p = Parser::new("foObar");
+        assert_eq!(Some("foOb"), p.slice_to_including('b'));
+        assert_eq!(Some((4, 'a')), p.cur.next());
+
+        let mut p = Parser::new("foObar");
+        assert_eq!(Some("f"), p.slice_to_including('f'));
+        assert_eq!(Some((1, 'o')), p.cur.next());
+    }
+
+    #[test]
+    fn slice_to_exc() {
+        let mut p = Parser::new("foObar");
+        assert_eq!(Some("foO"), p.slice_to_excluding('b'));
+        assert_eq!(Some((4, 'a')), p.cur.next());
+
+        let mut p = Parser::new("foObar");
+        assert_eq!(Some(""), p.slice_to_excluding('f'));
+        assert_eq!(Some((1, 'o')), p.cur.next());
+
+        let mut p = Parser::new("f\\oobar");
+        assert_eq!(Some("f\\o"), p.slice_to_excluding('o'));
+        assert_eq!(Some((4, 'b')), p.cur.next());
+    }
+
+    #[test]
+    fn slice_while() {
+        let mut p = Parser::new("foObar");
+        assert_eq!(Some("foO"), p.slice_while(|c| c != 'b'));
+        assert_eq!(Some((3, 'b')), p.cur.next());
+
+        let mut p = Parser::new("foObar");
+        assert_eq!(None, p.slice_while(|c| c != 'f'));
+        assert_eq!(Some((0, 'f')), p.cur.next());
+    }
+
+    #[test]
+    fn parse() {
+        let raw = r#"
+                [dict]
+                first = "first"
+                # comment
+                second ="another"
+                whitespace = "  "
+                empty = ""
+                some_bool = true
+
+                ary = [ "col1", 2,"col3", false]
+
+                [table]
+
+                |abc|def|
+                |---|---|
+                |one|two|
+                # comment
+                |  1| 2 |
+                |  2| 3 |
+
+                [three]
+                a=1
+                B=2
+                | this |
+            "#;
+
+        let mut p = Parser::new(raw);
+
+        assert_eq!(Some(Element::Section("dict".to_owned())), p.next());
+        assert_eq!(
+            Some(Entry("first".to_owned(), Value::String("first".to_owned()))),
+            p.next()
+        );
+        assert_eq!(Some(Comment(" comment\n".to_owned())), p.next());
+        assert_eq!(
+            Some(Entry(
+                "second".to_owned(),
+                Value::String("another".to_owned())
+            )),
+            p.next()
+        );
+        assert_eq!(
+            Some(Entry(
+                "whitespace".to_owned(),
+                Value::String("  ".to_owned())
+            )),
+            p.next()
+        );
+        assert_eq!(
+            Some(Entry("empty".to_owned(), Value::String("".to_owned()))),
+            p.next()
+        );
+        assert_eq!(
+            Some(Entry("some_bool".to_owned(), Value::Boolean(true))),
+            p.next()
+        );
+        assert_eq!(
+            Some(Entry(
+                "ary".to_owned(),
+                Value::Array(vec![
+                    Value::String("col1".to_owned()),
+                    Value::Integer(2),
+                    Value::String("col3".to_owned()),
+                    Value::Boolean(false)
+                ])
+            )),
+            p.next()
+        );
+
+        assert_eq!(Some(Element::Section("table".to_owned())), p.next());
+        assert_eq!(
+            Some(Row(vec![
+                Value::String("abc".to_owned()),
+                Value::String("def".to_owned())
+            ])),
+            p.next()
+        );
+        assert_eq!(
+            Some(Row(vec![
+                Value::String("---".to_owned()),
+                Value::String("---".to_owned())
+            ])),
+            p.next()
+        );
+        assert_eq!(
+            Some(Row(vec![
+                Value::String("one".to_owned()),
+                Value::String("two".to_owned())
+            ])),
+            p.next()
+        );
+        assert_eq!(Some(Comment(" comment\n".to_owned())), p.next());
+        assert_eq!(
+            Some(Row(vec![
+                Value::String("1".to_owned()),
+                Value::String("2".to_owned())
+            ])),
+            p.next()
+        );
+        assert_eq!(
+            Some(Row(vec![
+                Value::String("2".to_owned()),
+                Value::String("3".to_owned())
+            ])),
+            p.next()
+        );
+        assert_eq!(Some(Element::Section("three".to_owned())), p.next());
+        assert_eq!(Some(Entry("a".to_owned(), Value::Integer(1))), p.next());
+        assert_eq!(Some(Entry("B".to_owned(), Value::Integer(2))), p.next());
+        assert_eq!(Some(Row(vec![Value::String("this".to_owned())])), p.next());
+        assert_eq!(None, p.next());
+        assert_eq!(None, p.next());
+    }
+
+    #[test]
+    fn strict_comment_column() {
+        let raw = "# top\n| a | #1 |\n";
+
+        let mut p = Parser::new(raw);
+        assert_eq!(Some(Comment(" top\n".to_owned())), p.next());
+        assert_eq!(Some(Row(vec![Value::new_string("a")])), p.next());
+
+        let mut p = Parser::new(raw).with_strict_comment_column(true);
+        assert_eq!(Some(Comment(" top\n".to_owned())), p.next());
+        assert_eq!(
+            Some(Row(vec![Value::new_string("a"), Value::new_string("#1")])),
+            p.next()
+        );
+        assert_eq!(None, p.next());
+
+        let mut p = Parser::new("  # indented\n").with_strict_comment_column(true);
+        assert_eq!(None, p.next());
+        assert_eq!("A comment must start in column 1", p.errors[0].desc);
+
+        for raw in ["a = 1\n  # c\nb = 2\n", "a = 1 # c\nb = 2\n"] {
+            let mut p = Parser::new(raw).with_strict_comment_column(true);
+            let (map, errors) = p.read_with_errors();
+            assert_eq!(1, errors.len());
+            assert_eq!("A comment must start in column 1", errors[0].desc);
+            assert_eq!(Some(&Value::Integer(2)), map["root"].get("b"));
+        }
+
+        let mut p = Parser::new("a = 1 # c\nb = 2\n")
+            .with_strict_comment_column(true)
+            .with_warn_trailing(true);
+        let map = p.read().unwrap();
+        assert_eq!(Some(&Value::Integer(2)), map["root"].get("b"));
+        assert_eq!("Unexpected trailing data: # c", p.warnings()[0].desc);
+
+        let mut p = Parser::new("a = 1 # c\n")
+            .with_strict_comment_column(true)
+            .with_strict_trailing(true);
+        assert_eq!(None, p.read());
+        assert_eq!("Unexpected trailing data: # c", p.errors[0].desc);
+    }
+
+    #[test]
+    fn section_spans() {
+        let raw = "root = 1\n[A]\na = 1\n\n  [B]\n| x |\n[C]\nc = \"[c]\"\n";
+        let spans = Parser::new(raw).section_spans();
+
+        let slices: Vec<_> = spans
+            .iter()
+            .map(|(name, span)| (name.as_str(), &raw[span.clone()]))
+            .collect();
+        assert_eq!(
+            vec![
+                ("A", "[A]\na = 1\n\n  "),
+                ("B", "[B]\n| x |\n"),
+                ("C", "[C]\nc = \"[c]\"\n"),
+            ],
+            slices
+        );
+
+        let spans = Parser::new_filtered(raw, vec!["A", "C"]).section_spans();
+        let slices: Vec<_> = spans
+            .iter()
+            .map(|(name, span)| (name.as_str(), &raw[span.clone()]))
+            .collect();
+        assert_eq!(
+            vec![("A", "[A]\na = 1\n\n  "), ("C", "[C]\nc = \"[c]\"\n")],
+            slices
+        );
+
+        let spans = Parser::new_filtered(raw, vec!["A"]).section_spans();
+        assert_eq!(vec![("A".to_owned(), 9..22)], spans);
+    }
+
+    #[test]
+    fn identifier_rules() {
+        let raw = "[_Section1]\nkey_2 = 1\n";
+        let mut p = Parser::new(raw).with_identifier_rules(IdentRules::Identifier);
+        assert!(p.read().is_some());
+
+        for (raw, desc) in [
+            ("[ ]\n", "Invalid section name"),
+            ("[DEF.HOTEL]\n", "Invalid section name"),
+            ("[A]\n1key = 1\n", "Invalid key"),
+            ("[A]\nkey-2 = 1\n", "Invalid key"),
+            ("[A]\na$b = 1\n", "Invalid key"),
+        ] {
+            let mut p = Parser::new(raw).with_identifier_rules(IdentRules::Identifier);
+            assert_eq!(None, p.read(), "{raw}");
+            assert_eq!(desc, p.errors[0].desc);
+        }
+
+        let mut p =
+            Parser::new("[DEF.HOTEL]\n").with_identifier_rules(IdentRules::Custom(|name| {
+                name.split('.').all(|part| !part.is_empty())
+            }));
+        assert!(p.read().unwrap().contains_key("DEF.HOTEL"));
+
+        let mut p = Parser::new("[1]\nkey-2 = 1\n");
+        assert!(p.read().is_some());
+    }
+
+    #[test]
+    fn boolean_columns() {
+        let raw = r#"
+            | flag | name  | other |
+            |------|-------|-------|
+            | true | true  | false |
+            | false| x     | y     |
+            | yes  | z     |       |
+        "#;
+
+        let mut p = Parser::new(raw).with_boolean_columns("root", &[0, 2]);
+        let map = p.read().unwrap();
+        let rows = &map["root"].rows;
+        assert_eq!(Value::new_string("flag"), rows[0][0]);
+        assert_eq!(Value::new_string("------"), rows[1][0]);
+        assert_eq!(
+            vec![
+                Value::Boolean(true),
+                Value::new_string("true"),
+                Value::Boolean(false)
+            ],
+            rows[2]
+        );
+        assert_eq!(Value::Boolean(false), rows[3][0]);
+        assert_eq!(Value::new_string("y"), rows[3][2]);
+        assert_eq!(Value::new_string("yes"), rows[4][0]);
+        assert_eq!(Value::new_string(""), rows[4][2]);
+
+        let mut p = Parser::new(raw)
+            .with_boolean_columns("root", &[0])
+            .with_strict_boolean_columns(true);
+        assert_eq!(None, p.read());
+        assert_eq!("Cannot read a boolean", p.errors[0].desc);
+
+        let raw = "[A]\n| true | true |\n[B]\n| true | true |\n";
+        let mut p = Parser::new(raw)
+            .with_boolean_columns("B", &[1])
+            .with_strict_boolean_columns(true);
+        let map = p.read().unwrap();
+        assert_eq!(Value::new_string("true"), map["A"].rows[0][1]);
+        assert_eq!(
+            vec![Value::new_string("true"), Value::Boolean(true)],
+            map["B"].rows[0]
+        );
+    }
+
+    #[test]
+    fn require_section_header() {
+        for raw in ["key = 1\n[A]\n", "| a |\n[A]\n"] {
+            let mut p = Parser::new(raw).with_require_section_header(true);
+            assert_eq!(None, p.read());
+            assert_eq!("Expected a section header", p.errors[0].desc);
+            assert_eq!(0, p.errors[0].lo);
+
+            let mut p = Parser::new(raw);
+            assert!(p.read().is_some());
+        }
+
+        let raw = "# comment\n[A]\nkey = 1\n| a |\n";
+        let mut p = Parser::new(raw).with_require_section_header(true);
+        let section = p.read().unwrap().remove("A").unwrap();
+        assert_eq!(Some(&Value::Integer(1)), section.get("key"));
+        assert_eq!(1, section.rows.len());
+    }
+
+    #[test]
+    fn nth_section() {
+        let raw = r#"
+            [LOG]
+            n = 0
+            | a |
+            [OTHER]
+            n = 9
+            [LOG]
+            n = 1
+            | b |
+        "#;
+
+        let first = Parser::nth_section(raw, "LOG", 0).unwrap();
+        assert_eq!(Some(&Value::Integer(0)), first.get("n"));
+        assert_eq!(vec![vec![Value::new_string("a")]], first.rows);
+
+        let second = Parser::nth_section(raw, "LOG", 1).unwrap();
+        assert_eq!(Some(&Value::Integer(1)), second.get("n"));
+        assert_eq!(vec![vec![Value::new_string("b")]], second.rows);
+
+        assert_eq!(None, Parser::nth_section(raw, "LOG", 2));
+        assert_eq!(None, Parser::nth_section(raw, "MISSING", 0));
+    }
+
+    #[test]
+    fn read_section_map() {
+        let raw = r#"
+            a = 0
+            [CONTRACT]
+            id = "HOTEL001"
+            active = true
+            | code | name |
+            [DEF.MEAL]
+            id = "MEAL"
+            [CONTRACT]
+            id = "HOTEL002"
+        "#;
+
+        let map = Parser::read_section_map(raw, "CONTRACT").unwrap();
+        assert_eq!(2, map.len());
+        assert_eq!(Some(&Value::new_string("HOTEL001")), map.get("id"));
+        assert_eq!(Some(&Value::Boolean(true)), map.get("active"));
+
+        let map = Parser::read_section_map(raw, "DEF.MEAL").unwrap();
+        assert_eq!(
+            vec![("id", &Value::new_string("MEAL"))],
+            map.iter().map(|(k, v)| (k.as_str(), v)).collect::<Vec<_>>()
+        );
+
+        assert_eq!(None, Parser::read_section_map(raw, "MISSING"));
+        assert_eq!(None, Parser::read_section_map("[A]\nb = ?", "A"));
+    }
+
+    #[test]
+    fn directive() {
+        let raw = "%version 2\n%custom  a b  \n%bare\n[A]\nkey = 1\n| 50% |\n";
+        let mut p = Parser::new(raw);
+        assert_eq!(
+            Some(Element::Directive("version".to_owned(), "2".to_owned())),
+            p.next()
+        );
+        assert_eq!(
+            Some(Element::Directive("custom".to_owned(), "a b".to_owned())),
+            p.next()
+        );
+        assert_eq!(
+            Some(Element::Directive("bare".to_owned(), "".to_owned())),
+            p.next()
+        );
+        assert_eq!(Some(Element::Section("A".to_owned())), p.next());
+        assert_eq!(Some(Entry("key".to_owned(), Value::Integer(1))), p.next());
+        assert_eq!(Some(Row(vec![Value::new_string("50%")])), p.next());
+
+        let mut p = Parser::new(raw);
+        assert!(p.read().is_some());
+        assert_eq!(3, p.directives.len());
+    }
+
+    #[test]
+    fn trailing_data() {
+        let raw = "x = 1 garbage  \ny = 2   # ok\nz = [1] \n";
+
+        let mut p = Parser::new(raw).with_warn_trailing(true);
+        let section = p.read().unwrap().remove("root").unwrap();
+        assert_eq!(3, section.dictionary.len());
+        assert_eq!(1, p.warnings().len());
+        assert_eq!("Unexpected trailing data: garbage", p.warnings()[0].desc);
+        assert_eq!(6..13, p.warnings()[0].lo..p.warnings()[0].hi);
+
+        let mut p = Parser::new(raw).with_strict_trailing(true);
+        assert_eq!(None, p.read());
+        assert_eq!("Unexpected trailing data: garbage", p.errors[0].desc);
+
+        let mut p = Parser::new("y = 2   # ok\nz = { a = 1 }").with_strict_trailing(true);
+        assert!(p.read().is_some());
+        assert!(p.warnings().is_empty());
+    }
+
+    #[test]
+    fn read_with_attached_comments() {
+        let raw = "# file\n\n# about A\n[A]\n# the key\nkey = 1 # inline\nother = 2\n  # indented\n\n| x | # in row\n| y |\n[B]\n| z |\n# end\n";
+
+        let (map, attached) = Parser::new(raw).read_with_attached_comments().unwrap();
+
+        assert_eq!(Parser::new(raw).read().unwrap(), map);
+        assert_eq!(
+            vec![
+                (
+                    CommentTarget::Section("A".to_owned()),
+                    vec![" file\n".to_owned(), " about A\n".to_owned()]
+                ),
+                (
+                    CommentTarget::Entry {
+                        section: "A".to_owned(),
+                        key: "key".to_owned()
+                    },
+                    vec![" the key\n".to_owned(), " inline\n".to_owned()]
+                ),
+                (
+                    CommentTarget::Row {
+                        section: "A".to_owned(),
+                        index: 0
+                    },
+                    vec![" indented\n".to_owned()]
+                ),
+                (CommentTarget::End, vec![" end\n".to_owned()]),
+            ],
+            attached
+        );
+
+        let raw = "# root\na = 1\n";
+        let (_, attached) = Parser::new(raw).read_with_attached_comments().unwrap();
+        assert_eq!(
+            vec![(
+                CommentTarget::Entry {
+                    section: "root".to_owned(),
+                    key: "a".to_owned()
+                },
+                vec![" root\n".to_owned()]
+            )],
+            attached
+        );
+    }
+
+    #[test]
+    fn read_borrowed() {
+        use std::borrow::Cow;
+
+        let raw = r#"
+            [R]
+            a = "plain"
+            b = "with \"escapes\""
+            c = 1
+            d = ["nested"]
+            [A]
+            | x  | y\|z |
+            |----|------|
+            | ok | true |
+        "#;
+
+        let mut p = Parser::new(raw).with_boolean_columns("A", &[1]);
+        let map = p.read_borrowed().unwrap();
+        let root = &map["R"];
+
+        assert!(matches!(
+            root.get("a"),
+            Some(ValueRef::String(Cow::Borrowed("plain")))
+        ));
+        assert!(
+            matches!(root.get("b"), Some(ValueRef::String(Cow::Owned(s))) if s == "with \"escapes\"")
+        );
+        assert_eq!(Some(&ValueRef::Owned(Value::Integer(1))), root.get("c"));
+        assert_eq!(
+            Some(&ValueRef::Owned(Value::Array(vec![Value::new_string(
+                "nested"
+            )]))),
+            root.get("d")
+        );
 
-                &self.input[start..]
-            }
-        })
+        let rows = &map["A"].rows;
+        assert!(matches!(rows[0][0], ValueRef::String(Cow::Borrowed("x"))));
+        assert!(matches!(&rows[0][1], ValueRef::String(Cow::Owned(s)) if s == "y|z"));
+        assert_eq!(ValueRef::Owned(Value::Boolean(true)), rows[2][1]);
+
+        let owned: BTreeMap<_, _> = map
+            .into_iter()
+            .map(|(name, section)| (name, section.into_owned()))
+            .collect();
+        let mut p = Parser::new(raw).with_boolean_columns("A", &[1]);
+        assert_eq!(p.read().unwrap(), owned);
+
+        let mut p = Parser::new("a = \"x\"\nb = ?\n");
+        assert_eq!(None, p.read_borrowed());
+        assert_eq!("Cannot read a value", p.errors()[0].desc);
     }
 
-    fn slice_while(&mut self, predicate: impl Fn(char) -> bool) -> Option<&str> {
-        self.cur.peek().cloned().and_then(|(start, c)| {
-            if !predicate(c) {
-                None
-            } else {
-                self.cur.next();
+    #[test]
+    fn read_with_comments() {
+        let raw = "# head\n[A]\nkey = 1 # inline\n  # indented\n| x | # in row\n";
 
-                while let Some(&(end, c)) = self.cur.peek() {
-                    if !predicate(c) {
-                        return Some(&self.input[start..end]);
-                    }
+        let (map, comments) = Parser::new(raw).read_with_comments().unwrap();
 
-                    self.cur.next();
-                }
+        assert_eq!(Parser::new(raw).read().unwrap(), map);
+        assert_eq!(
+            vec![
+                (0, " head\n".to_owned()),
+                (19, " inline\n".to_owned()),
+                (30, " indented\n".to_owned()),
+            ],
+            comments
+        );
+        assert!(raw[30..].starts_with("# indented"));
+        assert_eq!(None, Parser::new("a = ?").read_with_comments());
+    }
 
-                Some(&self.input[start..])
-            }
-        })
+    #[test]
+    fn array_sections() {
+        let raw = r#"
+            [[item]]
+            id = 1
+            | a |
+            [other]
+            x = 0
+            [[item]]
+            id = 2
+        "#;
+
+        let mut p = Parser::new(raw).with_array_sections(true);
+        let map = p.read().unwrap();
+        assert_eq!(vec!["other"], map.keys().collect::<Vec<_>>());
+
+        let items = &p.section_arrays()["item"];
+        assert_eq!(2, items.len());
+        assert_eq!(Some(&Value::Integer(1)), items[0].get("id"));
+        assert_eq!(vec![vec![Value::new_string("a")]], items[0].rows);
+        assert_eq!(Some(&Value::Integer(2)), items[1].get("id"));
+        assert!(items[1].rows.is_empty());
+
+        let mut p = Parser::new(raw);
+        let (map, errors) = p.read_with_errors();
+        assert!(map.contains_key("[item"));
+        assert_eq!("Expected a key", errors[0].desc);
+        assert!(p.section_arrays().is_empty());
     }
 
-    fn add_error(&mut self, message: &str) {
-        let mut it = self.cur.clone();
-        let lo = it.next().map(|p| p.0).unwrap_or(self.input.len());
-        let hi = it.next().map(|p| p.0).unwrap_or(self.input.len());
+    #[test]
+    fn special_floats() {
+        let raw = "a = inf\nb = -inf\nc = nan\nd = [inf, 1.5, nan]\n";
 
-        self.errors.push(ParserError {
-            lo,
-            hi,
-            desc: message.to_owned(),
-        });
+        let mut p = Parser::new(raw).with_special_floats(true);
+        let section = p.read().unwrap().remove("root").unwrap();
+        assert_eq!(Some(f64::INFINITY), section.get("a").unwrap().as_float());
+        assert_eq!(
+            Some(f64::NEG_INFINITY),
+            section.get("b").unwrap().as_float()
+        );
+        assert!(section.get("c").unwrap().as_float().unwrap().is_nan());
+        assert_eq!("[ inf, 1.5, nan ]", section.get("d").unwrap().to_string());
+
+        let displayed = section.to_string();
+        let mut p = Parser::new(&displayed).with_special_floats(true);
+        let reparsed = p.read().unwrap().remove("root").unwrap();
+        assert_eq!(displayed, reparsed.to_string());
+
+        for raw in ["a = inf", "a = -inf", "a = nan"] {
+            let mut p = Parser::new(raw);
+            assert_eq!(None, p.read());
+            assert_eq!("Cannot read a value", p.errors[0].desc);
+        }
+
+        let mut p = Parser::new("a = infinity").with_special_floats(true);
+        assert_eq!(None, p.read());
     }
-}
 
-#[derive(Clone, Debug)]
-pub struct ParserError {
-    pub lo: usize,
-    pub hi: usize,
-    pub desc: String,
-}
+    #[test]
+    fn boolean() {
+        let mut p = Parser::new("true");
+        assert_eq!(Some(Value::Boolean(true)), p.value());
+
+        let mut p = Parser::new("false, 1");
+        assert_eq!(Some(Value::Boolean(false)), p.value());
+        assert_eq!(Some((5, ',')), p.cur.next());
+
+        for raw in ["trueish", "falsey", "truth", "tru"] {
+            let mut p = Parser::new(raw);
+            assert_eq!(None, p.value());
+            assert_eq!("Cannot read a value", p.errors[0].desc);
+            assert_eq!(Some((0, raw.chars().next().unwrap())), p.cur.next());
+        }
 
-impl error::Error for ParserError {
-    fn description(&self) -> &str {
-        "error parsing Ion"
+        let mut p = Parser::new("a = [true, false]\nb = trueish\n");
+        assert_eq!(None, p.read());
+        assert_eq!(
+            ("Cannot read a value", 22),
+            (p.errors[0].desc.as_str(), p.errors[0].lo)
+        );
     }
-}
 
-impl fmt::Display for ParserError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Debug::fmt(self, f)
+    #[test]
+    fn null() {
+        let raw = "a = null\nb = [null, 1]\nc = { x = null }\nnullable = null # comment\n";
+
+        let mut p = Parser::new(raw);
+        let section = p.read().unwrap().remove("root").unwrap();
+        assert_eq!(Some(&Value::Null), section.get("a"));
+        assert_eq!(
+            Some(&Value::Array(vec![Value::Null, Value::Integer(1)])),
+            section.get("b")
+        );
+        assert_eq!(Some(&Value::Null), section.get("nullable"));
+
+        let displayed = section.to_string();
+        assert!(displayed.contains("a = null\n"), "{displayed}");
+        let reparsed = Parser::new(&displayed)
+            .read()
+            .unwrap()
+            .remove("root")
+            .unwrap();
+        assert_eq!(section, reparsed);
+
+        for raw in ["a = nullable", "a = nul", "a = null1"] {
+            let mut p = Parser::new(raw);
+            assert_eq!(None, p.read());
+            assert_eq!("Cannot read a value", p.errors[0].desc);
+            assert_eq!(4, p.errors[0].lo);
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::Element::{self, Comment, Entry, Row};
-    use crate::{Dictionary, Parser, Section, Value};
-    use std::collections::BTreeMap;
+    #[test]
+    fn lazy_values() {
+        let raw = r#"
+            [A]
+            s = "a \" # b" # comment
+            n = 4.5
+            arr = [1, "x]", [2]]
+            dict = {
+                a = { b = "}" }
+                c = 1
+            }
+            bad = [1, ?]
+        "#;
+
+        let mut p = Parser::new(raw).with_lazy_values(true);
+        let lazy = p.read().unwrap().remove("A").unwrap();
+        let valid = raw.replace("bad = [1, ?]", "");
+        let mut p = Parser::new(&valid);
+        let eager = p.read().unwrap().remove("A").unwrap();
+
+        assert_eq!(5, lazy.dictionary.len());
+
+        for (key, value) in &eager.dictionary {
+            let lazy_value = &lazy.dictionary[key];
+            assert!(matches!(lazy_value, Value::Lazy(_)), "{key}");
+            assert_eq!(value, &lazy_value.resolve(raw).unwrap(), "{key}");
+        }
+
+        let err = lazy.get("bad").unwrap().resolve(raw).unwrap_err();
+        assert_eq!("Cannot read a value", err.desc);
+        assert_eq!(raw.find('?').unwrap(), err.lo);
+
+        assert_eq!(Value::Integer(1), Value::Integer(1).resolve(raw).unwrap());
+
+        for span in [0..100, 1..2] {
+            let err = Value::Lazy(span).resolve("aé").unwrap_err();
+            assert_eq!("Span is outside of the input", err.desc);
+        }
+    }
 
     #[test]
-    fn finish_string() {
-        let mut p = Parser::new("\"foObar\"");
-        assert_eq!(Some("foObar"), p.finish_string().unwrap().as_str());
+    fn indent_nesting() {
+        let raw = "[A]\nouter\n  a = 1\n\n  # comment\n  inner\n    b = \"x\"\n    c = [1, 2]\n  d = true\nflat = 2\n";
 
-        let mut p = Parser::new("\"foObar");
-        assert_eq!(Some("foObar"), p.finish_string().unwrap().as_str());
+        let mut p = Parser::new(raw).with_indent_nesting(true);
+        let section = p.read().unwrap().remove("A").unwrap();
+        assert_eq!(2, section.dictionary.len());
+        assert_eq!(
+            "{ a = 1, d = true, inner = { b = \"x\", c = [ 1, 2 ] } }",
+            section.get("outer").unwrap().to_string()
+        );
+        assert_eq!(Some(2), section.get("flat").unwrap().as_integer());
 
-        let mut p = Parser::new("\"\"");
-        assert_eq!(Some(""), p.finish_string().unwrap().as_str());
+        let mut p = Parser::new("[A]\nempty\nflat = 2\n").with_indent_nesting(true);
+        let section = p.read().unwrap().remove("A").unwrap();
+        assert_eq!("{  }", section.get("empty").unwrap().to_string());
 
-        let mut p = Parser::new("");
-        assert_eq!(None, p.finish_string());
+        let mut p = Parser::new("[A]\nouter\n  a = 1\n   b = 2\n").with_indent_nesting(true);
+        assert_eq!(None, p.read());
+        assert_eq!("Inconsistent indentation", p.errors[0].desc);
+
+        let mut p = Parser::new("[A]\nouter\n\ta = 1\n").with_indent_nesting(true);
+        assert_eq!(None, p.read());
+        assert_eq!("Indentation must use spaces", p.errors[0].desc);
+
+        let flat = "[A]\n  a = 1\n    b = 2\n";
+        let mut p = Parser::new(flat).with_indent_nesting(true);
+        let nested = p.read().unwrap();
+        let mut p = Parser::new(flat);
+        assert_eq!(Some(nested), p.read());
+
+        let mut p = Parser::new("[A]\nouter\n  a = 1\n");
+        assert_eq!(None, p.read());
     }
 
     #[test]
-    fn finish_array() {
-        let mut p = Parser::new("[\"a\"");
-        assert_eq!(None, p.finish_array());
+    fn max_line_length() {
+        let long = format!("[A]\na = 1\nb = \"{}\"\nc = 2\n", "x".repeat(100));
 
-        let mut p = Parser::new("[");
-        assert_eq!(None, p.finish_array());
+        let mut p = Parser::new(&long).with_max_line_length(20);
+        assert_eq!(None, p.read());
+        assert_eq!("Line exceeds the maximum length of 20", p.errors[0].desc);
+        assert_eq!((10, 116), (p.errors[0].lo, p.errors[0].hi));
 
-        let mut p = Parser::new("[]");
-        assert_eq!(Some(Value::Array(vec![])), p.finish_array());
+        let mut p = Parser::new(&long).with_max_line_length(106);
+        assert!(p.read().is_some());
 
-        let mut p = Parser::new("[\"a\"]");
-        assert_eq!(Some(Value::new_string_array("a")), p.finish_array());
+        let unterminated = format!("[A]\na = \"x\nb = 1\n{}", "c = 2\n".repeat(100));
+        let mut p = Parser::new(&unterminated).with_max_line_length(20);
+        assert_eq!(None, p.read());
+        assert_eq!(
+            "String exceeds the maximum line length of 20",
+            p.errors[0].desc
+        );
+        assert_eq!(8, p.errors[0].lo);
+
+        let mut p = Parser::new("[A]\r\na = \"xx\"\r\n").with_max_line_length(8);
+        assert!(p.read().is_some());
+
+        let mut p = Parser::new(&unterminated);
+        assert!(p.read().is_some());
     }
 
     #[test]
-    fn finish_dictionary() {
-        let mut p = Parser::new("{");
-        assert_eq!(None, p.finish_dictionary());
+    fn spanned() {
+        let input = include_str!("../tests/data/test.ion");
+        let lines: Vec<_> = input.lines().filter(|l| !l.is_empty()).collect();
+        let spanned: Vec<_> = Parser::new(input).spanned().collect();
 
-        let mut p = Parser::new("{ foo");
-        assert_eq!(None, p.finish_dictionary());
+        assert_eq!(lines.len(), spanned.len());
 
-        let mut p = Parser::new("{ foo = ");
-        assert_eq!(None, p.finish_dictionary());
+        for (line, (_, span)) in lines.into_iter().zip(spanned) {
+            assert_eq!(Some(line), element_source(input, span));
+        }
 
-        let mut p = Parser::new("{ foo = \"bar\"");
-        assert_eq!(None, p.finish_dictionary());
+        let input = "%include a.ion  \n[[item]]\nd = {\n  a = 1\n}\r\n| x |  # c\n";
+        let sources: Vec<_> = Parser::new(input)
+            .with_array_sections(true)
+            .spanned()
+            .filter_map(|(_, span)| element_source(input, span))
+            .collect();
 
-        let mut p = Parser::new("{ foo = [\"bar\"");
-        assert_eq!(None, p.finish_dictionary());
+        assert_eq!(
+            vec![
+                "%include a.ion",
+                "[[item]]",
+                "d = {\n  a = 1\n}",
+                "| x |  # c"
+            ],
+            sources
+        );
+        assert_eq!(None, element_source("aé", 1..2));
+        assert_eq!(None, element_source("a", 0..100));
+    }
 
-        let mut p = Parser::new("{ foo = [\"bar\"]");
-        assert_eq!(None, p.finish_dictionary());
+    #[test]
+    fn two_pass() {
+        let input =
+            "| r |\n[A]\na = 1\n| 1 |\n  | 2 |\n[B]\nb = \"[x\"\n[C]\n| 1 |\n| 2 |\n| 3 |\n";
+
+        let mut p = Parser::new(input);
+        let single = p.read();
+        let mut p = Parser::new(input).with_two_pass(true);
+        let two_pass = p.read();
+        assert_eq!(single, two_pass);
+
+        let sections = two_pass.unwrap();
+        assert_eq!(2, sections["A"].rows.capacity());
+        assert_eq!(3, sections["C"].rows.capacity());
+
+        let (root, counts) = Parser::new(input).row_counts();
+        assert_eq!(1, root);
+        assert_eq!(vec![(6, 2), (30, 0), (43, 3)], counts);
+
+        let input = include_str!("../tests/data/test.ion");
+        let mut p = Parser::new(input);
+        let single = p.read();
+        let mut p = Parser::new(input).with_two_pass(true);
+        assert_eq!(single, p.read());
+    }
 
-        let mut p = Parser::new("{}");
+    #[test]
+    fn leading_dash_tokens() {
+        let raw = "-5 = 1\n-inf = -inf\n--a = { -1 = true }\n";
+
+        let mut p = Parser::new(raw).with_special_floats(true);
+        let section = p.read().unwrap().remove("root").unwrap();
+        assert_eq!(Some(1), section.get("-5").unwrap().as_integer());
         assert_eq!(
-            Some(Value::Dictionary(Dictionary::new())),
-            p.finish_dictionary()
+            Some(f64::NEG_INFINITY),
+            section.get("-inf").unwrap().as_float()
+        );
+        assert_eq!(
+            Some(true),
+            section.get("--a").unwrap().get("-1").unwrap().as_boolean()
         );
 
-        let mut p = Parser::new("{ foo = [\"bar\"] }");
+        let displayed = section.to_string();
+        let mut p = Parser::new(&displayed).with_special_floats(true);
+        assert_eq!(section, p.read().unwrap().remove("root").unwrap());
+
+        for special_floats in [false, true] {
+            let mut p = Parser::new("a = -5").with_special_floats(special_floats);
+            assert_eq!(None, p.read());
+            assert_eq!("Cannot read a value", p.errors[0].desc);
+        }
+
+        let mut p = Parser::new("-5 = -inf");
+        assert_eq!(None, p.read());
+    }
+
+    #[test]
+    fn boundary_checks() {
+        let raw = "[[łą]]\nz = \"ąę\"\nb = true ż\nc = b64\"w6U=\"\nd = 1ä\n| ą | ę |\n# ść\n%ą ę\ne = naną\n";
+
+        let mut p = Parser::new(raw)
+            .with_boundary_checks(true)
+            .with_lenient_numbers(true)
+            .with_base64(true)
+            .with_array_sections(true)
+            .with_special_floats(true)
+            .with_warn_trailing(true);
+        let elements: Vec<_> = p.by_ref().collect();
+
         assert_eq!(
-            "{ foo = [ \"bar\" ] }",
-            p.finish_dictionary().map(|d| d.to_string()).unwrap()
+            Some(&Element::ArraySection("łą".to_owned())),
+            elements.first()
         );
+        assert!(elements.contains(&Entry("z".to_owned(), Value::new_string("ąę"))));
+        assert!(elements.contains(&Entry("b".to_owned(), Value::Boolean(true))));
+        assert!(elements.contains(&Entry("c".to_owned(), Value::Bytes("å".into()))));
+        assert!(elements.contains(&Entry("d".to_owned(), Value::new_string("1ä"))));
+        assert!(elements.contains(&Element::Row(vec![
+            Value::new_string("ą"),
+            Value::new_string("ę")
+        ])));
+        assert!(elements.contains(&Comment(" ść\n".to_owned())));
+        assert!(elements.contains(&Element::Directive("ą".to_owned(), "ę".to_owned())));
+        assert_eq!("Cannot read a value", p.errors[0].desc);
+
+        let mut p = Parser::new("ą").with_boundary_checks(true);
+        assert_eq!("ą", p.slice(0..2));
+        assert_eq!("", p.slice(2..2));
+        assert!(p.errors.is_empty());
     }
 
     #[test]
-    fn slice_to_inc() {
-        let mut p = Parser::new("foObar");
-        assert_eq!(Some("foOb"), p.slice_to_including('b'));
-        assert_eq!(Some((4, 'a')), p.cur.next());
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "slice 0..1 is not on a char boundary of the input")]
+    fn boundary_checks_assert_in_debug() {
+        Parser::new("ą").with_boundary_checks(true).slice(0..1);
+    }
 
-        let mut p = Parser::new("foObar");
-        assert_eq!(Some("f"), p.slice_to_including('f'));
-        assert_eq!(Some((1, 'o')), p.cur.next());
+    #[test]
+    #[cfg(not(debug_assertions))]
+    fn boundary_checks_report_errors_in_release() {
+        let mut p = Parser::new("ą").with_boundary_checks(true);
+        assert_eq!("", p.slice(0..1));
+        assert_eq!("Slice is not on a char boundary", p.errors[0].desc);
     }
 
     #[test]
-    fn slice_to_exc() {
-        let mut p = Parser::new("foObar");
-        assert_eq!(Some("foO"), p.slice_to_excluding('b'));
-        assert_eq!(Some((4, 'a')), p.cur.next());
+    fn snippet() {
+        let input = "a = 1\nb = [1, zz, 3]\nc = 2\n";
+        let mut p = Parser::new(input);
+        p.read();
+        assert_eq!(
+            "b = [1, zz, 3]\n        ^ Cannot read a value",
+            p.errors[0].snippet(input)
+        );
 
-        let mut p = Parser::new("foObar");
-        assert_eq!(Some(""), p.slice_to_excluding('f'));
-        assert_eq!(Some((1, 'o')), p.cur.next());
+        let error = ParserError::new(input, 14, 16, "Bad item");
+        assert_eq!("b = [1, zz, 3]\n        ^^ Bad item", error.snippet(input));
 
-        let mut p = Parser::new("f\\oobar");
-        assert_eq!(Some("f\\o"), p.slice_to_excluding('o'));
-        assert_eq!(Some((4, 'b')), p.cur.next());
+        let input = "\tx = ?\r\n";
+        let error = ParserError::new(input, 5, 20, "Cannot read a value");
+        assert_eq!(
+            "    x = ?\n        ^ Cannot read a value",
+            error.snippet(input)
+        );
+
+        let error = ParserError::new("a = [", 5, 5, "Cannot finish an array");
+        assert_eq!(
+            "a = [\n     ^ Cannot finish an array",
+            error.snippet("a = [")
+        );
+
+        let input = "\tb = [1, zz]\r\nc = 1\n";
+        for (lo, hi) in [(7, 9), (11, 12), (12, 40)] {
+            let error = ParserError::new(input, lo, hi, "Bad item");
+            let snippet = error.snippet(input);
+            let rendered = error.render(input);
+            let carets = snippet
+                .lines()
+                .nth(1)
+                .unwrap()
+                .trim_end_matches(" Bad item");
+
+            assert!(rendered.contains(&format!("  | {carets}\n")), "{rendered}");
+        }
     }
 
     #[test]
-    fn slice_while() {
-        let mut p = Parser::new("foObar");
-        assert_eq!(Some("foO"), p.slice_while(|c| c != 'b'));
-        assert_eq!(Some((3, 'b')), p.cur.next());
+    fn render_error() {
+        let input = "[A]\na = 1\nb = ?\nc = 2\n[B]\n";
+        let mut p = Parser::new(input);
+        assert_eq!(None, p.read());
+
+        let expected = "\
+error: Cannot read a value
+ --> 3:5
+  |
+2 | a = 1
+3 | b = ?
+  |     ^
+4 | c = 2
+";
+        assert_eq!(expected, p.errors[0].render(input));
+
+        let input = "a = [1,\r\n\t2, ?\r\n]";
+        let error = ParserError::new(input, 4, 13, "Bad array");
+        let expected = "\
+error: Bad array
+ --> 1:5
+  |
+1 | a = [1,
+  |     ^^^
+2 |     2, ?
+  | ^^^^^^^
+3 | ]
+";
+        assert_eq!(expected, error.render(input));
+
+        let error = ParserError::new("a = 1", 5, 5, "Unexpected end");
+        let expected = "\
+error: Unexpected end
+ --> 1:6
+  |
+1 | a = 1
+  |      ^
+";
+        assert_eq!(expected, error.render("a = 1"));
+    }
 
-        let mut p = Parser::new("foObar");
-        assert_eq!(None, p.slice_while(|c| c != 'f'));
-        assert_eq!(Some((0, 'f')), p.cur.next());
+    #[test]
+    fn keyval_separators() {
+        let raw = "a := 1\nb => 2\nc = 3\nd = { e => 4 }\n";
+
+        let mut p = Parser::new(raw).with_keyval_separators(&["=", ":=", "=>"]);
+        let section = p.read().unwrap().remove("root").unwrap();
+        assert_eq!(Some(1), section.get("a").unwrap().as_integer());
+        assert_eq!(Some(2), section.get("b").unwrap().as_integer());
+        assert_eq!(Some(3), section.get("c").unwrap().as_integer());
+        assert_eq!(
+            Some(4),
+            section.get("d").unwrap().get("e").unwrap().as_integer()
+        );
+
+        let mut p = Parser::new("a := 1\n");
+        assert_eq!(None, p.read());
+        assert_eq!("Expected '='", p.errors[0].desc);
+
+        let mut p = Parser::new("b => 2\n");
+        assert_eq!(None, p.read());
+
+        let mut p = Parser::new("c = 3\n").with_keyval_separators(&[":=", "=>"]);
+        assert_eq!(None, p.read());
+        assert_eq!("Expected ':=' or '=>'", p.errors[0].desc);
     }
 
     #[test]
-    fn parse() {
-        let raw = r#"
-                [dict]
-                first = "first"
-                # comment
-                second ="another"
-                whitespace = "  "
-                empty = ""
-                some_bool = true
+    fn documents() {
+        let raw = "[A]\na = 1\n| x |\n|---|\n---\n[A]\na = 2\n---  \r\n[B]\nb = ?\n";
+        let documents: Vec<_> = Parser::documents(raw).collect();
 
-                ary = [ "col1", 2,"col3", false]
+        assert_eq!(3, documents.len());
 
-                [table]
+        let first = documents[0].as_ref().unwrap();
+        assert_eq!(Some(&Value::Integer(1)), first["A"].get("a"));
+        assert_eq!(2, first["A"].rows.len());
 
-                |abc|def|
-                |---|---|
-                |one|two|
-                # comment
-                |  1| 2 |
-                |  2| 3 |
+        let second = documents[1].as_ref().unwrap();
+        assert_eq!(Some(&Value::Integer(2)), second["A"].get("a"));
+        assert!(!second.contains_key("B"));
 
-                [three]
-                a=1
-                B=2
-                | this |
-            "#;
+        let errors = documents[2].as_ref().unwrap_err();
+        assert_eq!("Cannot read a value", errors[0].desc);
+        assert_eq!(raw.find('?').unwrap(), errors[0].lo);
+
+        let documents: Vec<_> = Parser::documents_with_delimiter("a = 1\n===\na = 2", "===")
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(2, documents.len());
+        assert_eq!(Some(&Value::Integer(2)), documents[1]["root"].get("a"));
+
+        assert_eq!(1, Parser::documents("[A]\na = 1\n").count());
+        assert_eq!(1, Parser::documents("").count());
+
+        let documents: Vec<_> = Parser::documents("a = 1\n---\nb = ?\n---\n  \n").collect();
+        assert_eq!(2, documents.len());
+        assert!(documents[1].is_err());
+    }
+
+    #[test]
+    fn section_chunks() {
+        let raw = "a = 1\n[A]\nb = 2\n| x |\n# comment\n[B]\n[C]\nc = 3\n[D]\nd = [1,\n";
+        let mut chunks = Parser::new(raw).section_chunks();
+
+        let (name, section) = chunks.next().unwrap().unwrap();
+        assert_eq!("root", name);
+        assert_eq!(Some(&Value::Integer(1)), section.get("a"));
+
+        let (name, section) = chunks.next().unwrap().unwrap();
+        assert_eq!("A", name);
+        assert_eq!(Some(&Value::Integer(2)), section.get("b"));
+        assert_eq!(vec![vec![Value::String("x".to_owned())]], section.rows);
+
+        let (name, section) = chunks.next().unwrap().unwrap();
+        assert_eq!("B", name);
+        assert_eq!(Section::new(), section);
+
+        let (name, section) = chunks.next().unwrap().unwrap();
+        assert_eq!("C", name);
+        assert_eq!(Some(&Value::Integer(3)), section.get("c"));
+
+        let error = chunks.next().unwrap().unwrap_err();
+        assert_eq!("Cannot read a value", error.desc);
+        assert!(chunks.next().is_none());
+    }
+
+    #[test]
+    fn section_chunks_root() {
+        let mut chunks = Parser::new("[A]\na = 1\n").section_chunks();
+        assert_eq!("A", chunks.next().unwrap().unwrap().0);
+        assert!(chunks.next().is_none());
+
+        let mut chunks = Parser::new("").section_chunks();
+        assert_eq!(
+            ("root".to_owned(), Section::new()),
+            chunks.next().unwrap().unwrap()
+        );
+        assert!(chunks.next().is_none());
+
+        let mut chunks =
+            Parser::new_filtered("a = 1\n[A]\nb = 2\n[B]\n", vec!["B"]).section_chunks();
+        assert_eq!("B", chunks.next().unwrap().unwrap().0);
+        assert!(chunks.next().is_none());
+    }
+
+    #[test]
+    fn reset() {
+        let first = "[A]\na = 1\n| x |\n[B]\nb = 2\n";
+        let second = "[A]\na = 3\n[C]\nc = 4\n";
+
+        let mut p = Parser::new_filtered(first, vec!["A"]).with_boolean_columns("A", &[0]);
+        let map = p.read().unwrap();
+        assert_eq!(
+            Parser::new_filtered(first, vec!["A"])
+                .with_boolean_columns("A", &[0])
+                .read(),
+            Some(map)
+        );
+
+        p.reset(second);
+        let map = p.read().unwrap();
+        assert_eq!(Parser::new_filtered(second, vec!["A"]).read(), Some(map));
+
+        p.reset("a = [");
+        assert!(p.read().is_none());
+        assert_eq!(1, p.errors.len());
+
+        p.reset(first);
+        assert!(p.read().is_some());
+        assert!(p.errors.is_empty());
+    }
+
+    #[test]
+    fn error_line_and_col() {
+        let raw = "[A]\r\na = 1\r\n\r\n[B]\r\n| x | y |\r\n\r\n[C]\r\nb = 2\r\nc = [1, ?]\r\n";
+        let mut p = Parser::new(raw);
+        assert_eq!(None, p.read());
+
+        let error = &p.errors[0];
+        assert_eq!((9, 9), (error.line, error.col));
+        assert_eq!("error at 9:9: Cannot read a value", error.to_string());
+
+        let mut p = Parser::new("a = \"zażółć\" ?").with_strict_trailing(true);
+        assert_eq!(None, p.read());
+        assert_eq!((1, 14), (p.errors[0].line, p.errors[0].col));
+
+        let mut p = Parser::new("a = [");
+        assert_eq!(None, p.read());
+        assert_eq!((1, 6), (p.errors[0].line, p.errors[0].col));
+
+        let raw = "a = 1\n---\nb = 2\nc = ?\n";
+        let errors = Parser::documents(raw).nth(1).unwrap().unwrap_err();
+        assert_eq!((4, 5), (errors[0].line, errors[0].col));
+    }
+
+    #[test]
+    fn skip_separator_rows() {
+        let raw = "[T]\n| a | b |\n|---|---|\n| 1 | - |\n|:--|--:|\n| :-: | --- |\n| - | x |\n| ---x | --- |\n|:|---|\n";
+
+        let map = Parser::new(raw)
+            .with_skip_separator_rows(true)
+            .read()
+            .unwrap();
+        let cells = |row: &[Value]| {
+            row.iter()
+                .map(|v| v.as_str().unwrap().to_owned())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec![
+                vec!["a", "b"],
+                vec!["1", "-"],
+                vec!["-", "x"],
+                vec!["---x", "---"],
+                vec![":", "---"],
+            ],
+            map["T"]
+                .rows
+                .iter()
+                .map(|row| cells(row))
+                .collect::<Vec<_>>()
+        );
+
+        let map = Parser::new(raw).read().unwrap();
+        assert_eq!(8, map["T"].rows.len());
+
+        let mut p = Parser::new("|---|\n")
+            .with_skip_separator_rows(true)
+            .with_typed_cells(true);
+        assert_eq!(None, p.next());
+        assert!(p.errors().is_empty());
+    }
+
+    #[test]
+    fn cell() {
+        use std::borrow::Cow;
+
+        let mut p = Parser::new("  plain cell  | a\\|b\\\\c  |\tż \t|");
+        assert!(matches!(p.cell(), Cow::Borrowed("plain cell")));
+        p.eat('|');
+        assert!(matches!(p.cell(), Cow::Owned(s) if s == "a|b\\c"));
+        p.eat('|');
+        assert!(matches!(p.cell(), Cow::Borrowed("ż")));
+
+        let raw = "| a | b\\|c |  d\\n  |\n";
+        assert_eq!(
+            Some(Row(vec![
+                Value::new_string("a"),
+                Value::new_string("b|c"),
+                Value::new_string("d\n"),
+            ])),
+            Parser::new(raw).next()
+        );
+    }
+
+    #[test]
+    fn max_depth() {
+        let raw = format!("a = {}\n", "[".repeat(100_000));
+        let mut p = Parser::new(&raw);
+        assert_eq!(None, p.read());
+        assert_eq!(
+            "Exceeded the maximum nesting depth of 128",
+            p.errors()[0].desc
+        );
+        assert_eq!(4 + 128, p.errors()[0].lo);
+
+        let raw = "a = [[1]]\nb = { c = { d = 1 } }\ne = [{ f = 1 }]\n";
+        assert!(Parser::new(raw).with_max_depth(2).read().is_some());
+
+        for raw in [
+            "a = [[[1]]]\n",
+            "a = { b = { c = { d = 1 } } }\n",
+            "a = [{ b = [1] }]\n",
+        ] {
+            let mut p = Parser::new(raw).with_max_depth(2);
+            assert_eq!(None, p.read(), "{raw}");
+            assert_eq!(
+                "Exceeded the maximum nesting depth of 2",
+                p.errors()[0].desc
+            );
+        }
 
-        let mut p = Parser::new(raw);
+        let raw = "a\n  b\n    c = 1\n";
+        let mut p = Parser::new(raw).with_indent_nesting(true).with_max_depth(1);
+        assert_eq!(None, p.read());
+    }
 
-        assert_eq!(Some(Element::Section("dict".to_owned())), p.next());
+    #[test]
+    fn inline_comments() {
+        let raw = r##"
+            port = 8080 # default
+            name = "x # y" # quoted
+            ary = [1, "#"]# no space
+            dict = { a = 1 } # after a dictionary
+            ratio = 1.5 #
+            flag = true # bool
+            none = null # null
+            text = """
+            multi # line
+            """ # after a multiline string
+        "##;
+
+        let section = Parser::new(raw).read().unwrap().remove("root").unwrap();
+
+        assert_eq!(8, section.dictionary.len());
+        assert_eq!(Some(&Value::Integer(8080)), section.get("port"));
+        assert_eq!(Some(&Value::new_string("x # y")), section.get("name"));
         assert_eq!(
-            Some(Entry("first".to_owned(), Value::String("first".to_owned()))),
-            p.next()
+            Some(&Value::Array(vec![
+                Value::Integer(1),
+                Value::new_string("#")
+            ])),
+            section.get("ary")
         );
-        assert_eq!(Some(Comment(" comment\n".to_owned())), p.next());
+        assert_eq!(Some(&Value::Float(1.5)), section.get("ratio"));
+        assert_eq!(Some(&Value::Boolean(true)), section.get("flag"));
+        assert_eq!(Some(&Value::Null), section.get("none"));
+
+        let mut p = Parser::new("a = [1] # one\nb = 2");
         assert_eq!(
-            Some(Entry(
-                "second".to_owned(),
-                Value::String("another".to_owned())
-            )),
+            Some(Entry("a".to_owned(), Value::Array(vec![Value::Integer(1)]))),
             p.next()
         );
+        assert_eq!(Some(Comment(" one\n".to_owned())), p.next());
+        assert_eq!(Some(Entry("b".to_owned(), Value::Integer(2))), p.next());
+
+        let raw = "a = [1, 2] # c\nb = { c = 1 } # d\n";
+        let mut p = Parser::new(raw).with_lazy_values(true);
+        let section = p.read().unwrap().remove("root").unwrap();
         assert_eq!(
-            Some(Entry(
-                "whitespace".to_owned(),
-                Value::String("  ".to_owned())
-            )),
-            p.next()
+            Some(Value::Array(vec![Value::Integer(1), Value::Integer(2)])),
+            section.get("a").unwrap().resolve(raw).ok()
         );
+    }
+
+    #[test]
+    fn strict_commas() {
+        let cases = [
+            ("a = [1,,2]\n", "Unexpected ','", 7),
+            ("a = [,1]\n", "Unexpected ','", 5),
+            ("a = [1,]\n", "Trailing ','", 6),
+            ("a = { b = 1,, c = 2 }\n", "Unexpected ','", 12),
+            ("a = { , b = 1 }\n", "Unexpected ','", 6),
+            ("a = { b = 1, }\n", "Trailing ','", 11),
+        ];
+
+        for (raw, desc, lo) in cases {
+            assert!(Parser::new(raw).read().is_some(), "{raw}");
+
+            let mut p = Parser::new(raw).with_strict_commas(true);
+            assert_eq!(None, p.read(), "{raw}");
+            assert_eq!((desc, lo), (p.errors()[0].desc.as_str(), p.errors()[0].lo));
+        }
+
+        let raw = "a = [1, [2, 3], { b = 1, c = [] }]\nd = {\n  e = 1,\n  f = 2\n  g = 3\n}\n";
         assert_eq!(
-            Some(Entry("empty".to_owned(), Value::String("".to_owned()))),
-            p.next()
+            Parser::new(raw).read(),
+            Parser::new(raw).with_strict_commas(true).read()
         );
+    }
+
+    #[test]
+    fn typed_cells() {
+        let raw = "| 1 | 2.5 | true | false | x |  | 0xff | 12abc | \"q\" | truex | [1] |\n";
+        let row = vec![
+            Value::Integer(1),
+            Value::Float(2.5),
+            Value::Boolean(true),
+            Value::Boolean(false),
+            Value::new_string("x"),
+            Value::new_string(""),
+            Value::Integer(255),
+            Value::new_string("12abc"),
+            Value::new_string("\"q\""),
+            Value::new_string("truex"),
+            Value::new_string("[1]"),
+        ];
+
+        let mut p = Parser::new(raw).with_typed_cells(true);
+        assert_eq!(Some(Row(row)), p.next());
+        assert!(p.errors().is_empty());
+
+        let mut p = Parser::new(raw);
+        let Some(Row(row)) = p.next() else { panic!() };
+        assert!(row.iter().all(Value::is_string));
+
+        let mut p = Parser::new("| 1 | 2 |\n")
+            .with_typed_cells(true)
+            .with_boolean_columns("root", &[1]);
         assert_eq!(
-            Some(Entry("some_bool".to_owned(), Value::Boolean(true))),
+            Some(Row(vec![Value::Integer(1), Value::new_string("2")])),
             p.next()
         );
+    }
+
+    #[test]
+    fn read_ordered() {
+        let raw = "[Z]\na = 1\n[A]\n| x |\n[M]\n";
+        let sections = Parser::new(raw).read_ordered().unwrap();
+        let names: Vec<_> = sections.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(vec!["Z", "A", "M"], names);
+        assert_eq!(Some(&Value::Integer(1)), sections[0].1.get("a"));
+
+        let raw = "[B]\nb = 1\n[A]\n[B]\nb = 2\n[C]\n";
+        let sections = Parser::new(raw).read_ordered().unwrap();
+        let names: Vec<_> = sections.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(vec!["A", "B", "C"], names);
+        assert_eq!(Some(&Value::Integer(2)), sections[1].1.get("b"));
+
+        let sections = Parser::new("a = 1\n").read_ordered().unwrap();
+        assert_eq!("root", sections[0].0);
+
+        let raw = "a = 1\n[B]\nb = 2\n[A]\n";
+        let sections = Parser::new(raw).read_ordered().unwrap();
+        let names: Vec<_> = sections.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(vec!["B", "A"], names);
         assert_eq!(
-            Some(Entry(
-                "ary".to_owned(),
-                Value::Array(vec![
-                    Value::String("col1".to_owned()),
-                    Value::Integer(2),
-                    Value::String("col3".to_owned()),
-                    Value::Boolean(false)
-                ])
-            )),
-            p.next()
+            Parser::new(raw).read().unwrap().keys().collect::<Vec<_>>(),
+            vec!["A", "B"]
         );
 
-        assert_eq!(Some(Element::Section("table".to_owned())), p.next());
+        assert_eq!(None, Parser::new("a = ?").read_ordered());
+    }
+
+    #[test]
+    fn from_bytes() {
+        let raw = "[MEAL]\nname = \"Śniadanie ☕\"\n| kod | opis |\n| ŻÓŁW | 🐢 |\n";
+        let map = Parser::from_bytes(raw.as_bytes()).unwrap().read().unwrap();
+
+        assert_eq!(Parser::new(raw).read().unwrap(), map);
         assert_eq!(
-            Some(Row(vec![
-                Value::String("abc".to_owned()),
-                Value::String("def".to_owned())
-            ])),
-            p.next()
+            Some(&Value::new_string("Śniadanie ☕")),
+            map["MEAL"].get("name")
+        );
+        assert_eq!(Value::new_string("🐢"), map["MEAL"].rows[1][1]);
+
+        let err = Parser::from_bytes(b"a = \"\xc5\"\n").err().unwrap();
+        assert_eq!(5, err.valid_up_to());
+    }
+
+    #[test]
+    fn read_from_reader() {
+        let raw = "[A]\na = 1\n| x |\n[B]\nb = \"ż\"\n";
+        let map = read_from(io::Cursor::new(raw)).unwrap();
+        assert_eq!(Parser::new(raw).read().unwrap(), map);
+
+        let err = read_from(io::Cursor::new("a = ?\n")).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(matches!(
+            err.get_ref().and_then(|e| e.downcast_ref()),
+            Some(IonError::ParserErrors(errors)) if errors.len() == 1
+        ));
+
+        let err = read_from(io::Cursor::new(b"a = \"\xff\"\n")).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+
+    #[test]
+    fn read_nested() {
+        let raw = r#"
+            [server]
+            name = "main"
+            [server.http]
+            port = 80
+            [server.http.tls]
+            cert = "a.pem"
+            ["weird.name"]
+            a = 1
+            [DEF]
+            | x |
+        "#;
+
+        let map = Parser::new(raw).read_nested().unwrap();
+
+        assert_eq!(
+            vec!["DEF", "server", "weird.name"],
+            map.keys().collect::<Vec<_>>()
+        );
+        assert_eq!(Some(&Value::Integer(1)), map["weird.name"].get("a"));
+        assert_eq!(1, map["DEF"].rows.len());
+
+        let server = &map["server"];
+        assert_eq!(Some(&Value::new_string("main")), server.get("name"));
+
+        let Some(Value::Dictionary(http)) = server.get("http") else {
+            panic!("http is not a dictionary: {:?}", server.get("http"));
+        };
+        assert_eq!(Some(&Value::Integer(80)), http.get("port"));
+
+        let Some(Value::Dictionary(tls)) = http.get("tls") else {
+            panic!("tls is not a dictionary: {:?}", http.get("tls"));
+        };
+        assert_eq!(Some(&Value::new_string("a.pem")), tls.get("cert"));
+
+        let map = Parser::new(
+            "[a.b]
+c = 1
+",
+        )
+        .read_nested()
+        .unwrap();
+        assert_eq!(None, map["a"].get("c"));
+        assert!(matches!(map["a"].get("b"), Some(Value::Dictionary(_))));
+
+        let mut p = Parser::new(
+            "[a]
+b = 1
+[a.b]
+c = 1
+",
+        );
+        assert_eq!(None, p.read_nested());
+        assert_eq!("Section conflicts with an entry", p.errors()[0].desc);
+        assert_eq!(3, p.errors()[0].line);
+
+        let mut p = Parser::new(
+            "[a.b]
+| x |
+",
         );
+        assert_eq!(None, p.read_nested());
+        assert_eq!("Cannot nest a section with rows", p.errors()[0].desc);
+    }
+
+    #[test]
+    fn read_with_errors() {
+        let raw = "[A]\na = 1\nb = [1, ?, 3]\nc = 2\n[B]\n| x | y |\nd = trueish\ne = \"\\u{110000}\"\nf = 3\n";
+
+        let mut p = Parser::new(raw);
+        let (map, errors) = p.read_with_errors();
+
         assert_eq!(
-            Some(Row(vec![
-                Value::String("---".to_owned()),
-                Value::String("---".to_owned())
-            ])),
-            p.next()
+            vec![
+                (3, "Cannot read a value"),
+                (7, "Cannot read a value"),
+                (8, "Invalid unicode escape")
+            ],
+            errors
+                .iter()
+                .map(|e| (e.line, e.desc.as_str()))
+                .collect::<Vec<_>>()
         );
+        assert_eq!(3, p.errors().len());
+
+        assert_eq!(Some(&Value::Integer(1)), map["A"].get("a"));
+        assert_eq!(None, map["A"].get("b"));
+        assert_eq!(Some(&Value::Integer(2)), map["A"].get("c"));
+        assert_eq!(1, map["B"].rows.len());
+        assert_eq!(Some(&Value::Integer(3)), map["B"].get("f"));
+
+        let mut p = Parser::new(raw);
+        assert_eq!(None, p.read());
+        assert_eq!(3, p.errors().len());
+
+        let mut p = Parser::new("a = 1\n");
+        let (map, errors) = p.read_with_errors();
+        assert!(errors.is_empty());
+        assert_eq!(Parser::new("a = 1\n").read().unwrap(), map);
+    }
+
+    #[test]
+    fn read_with_errors_reports_malformed_entries() {
+        let raw = "a = 1\nfoo bar\n@x\nb = 2\n";
+
+        let mut p = Parser::new(raw);
+        let (map, errors) = p.read_with_errors();
+
         assert_eq!(
-            Some(Row(vec![
-                Value::String("one".to_owned()),
-                Value::String("two".to_owned())
-            ])),
-            p.next()
+            vec![(2, "Expected '='"), (3, "Expected a key")],
+            errors
+                .iter()
+                .map(|e| (e.line, e.desc.as_str()))
+                .collect::<Vec<_>>()
         );
-        assert_eq!(Some(Comment(" comment\n".to_owned())), p.next());
+        assert_eq!(Some(&Value::Integer(1)), map["root"].get("a"));
+        assert_eq!(Some(&Value::Integer(2)), map["root"].get("b"));
+
+        assert_eq!(None, Parser::new(raw).read());
+    }
+
+    #[test]
+    fn filter_skips_whole_lines() {
+        let mut p = Parser::new_filtered("[A]\n[B]\nb = 1\n", vec!["B"]);
+        let map = p.read().unwrap();
+        assert_eq!(Some(&Value::Integer(1)), map["B"].get("b"));
+    }
+
+    #[test]
+    fn elements_or_errors() {
+        let raw = "[A]\na = 1\n| x |\n";
+        let elements: Vec<_> = Parser::new(raw).elements_or_errors().collect();
+        assert_eq!(3, elements.len());
+        assert!(elements.iter().all(Result::is_ok));
+
+        let raw = "[A]\na = 1\nb = ?\nc = 2\n";
+        let mut elements = Parser::new(raw).elements_or_errors();
         assert_eq!(
-            Some(Row(vec![
-                Value::String("1".to_owned()),
-                Value::String("2".to_owned())
-            ])),
-            p.next()
+            Some(Element::Section("A".to_owned())),
+            elements.next().unwrap().ok()
         );
         assert_eq!(
-            Some(Row(vec![
-                Value::String("2".to_owned()),
-                Value::String("3".to_owned())
-            ])),
-            p.next()
+            Some(Entry("a".to_owned(), Value::Integer(1))),
+            elements.next().unwrap().ok()
         );
-        assert_eq!(Some(Element::Section("three".to_owned())), p.next());
-        assert_eq!(Some(Entry("a".to_owned(), Value::Integer(1))), p.next());
-        assert_eq!(Some(Entry("B".to_owned(), Value::Integer(2))), p.next());
-        assert_eq!(Some(Row(vec![Value::String("this".to_owned())])), p.next());
-        assert_eq!(None, p.next());
-        assert_eq!(None, p.next());
+        let err = elements.next().unwrap().unwrap_err();
+        assert_eq!("Cannot read a value", err.desc);
+        assert_eq!(raw.find('?').unwrap(), err.lo);
+        assert!(elements.next().is_none());
     }
 
     #[test]
@@ -865,22 +4765,81 @@ mod tests {
                         expected.insert("root".to_owned(), sect);
                         assert_eq!(expected, actual);
                     }
+
+                    #[test]
+                    fn then_values_are_reachable_by_path() {
+                        let raw = r#"
+                            R75042 = {
+                            view = "SV"
+                            loc  = ["M", "B"]
+                            dist = { beach_km = 4.1 }
+                        }"#;
+                        let actual = Parser::new(raw).read().unwrap();
+                        let value = actual["root"].get("R75042").unwrap();
+
+                        assert_eq!(Some(&Value::Float(4.1)), value.get("dist.beach_km"));
+                        assert_eq!(Some(&Value::new_string("B")), value.get("loc.1"));
+                        assert_eq!(None, value.get("loc.2"));
+                        assert_eq!(None, value.get("dist.sea_km"));
+                        assert_eq!(
+                            Some(&Value::Float(4.1)),
+                            actual["root"].get("R75042.dist.beach_km")
+                        );
+                    }
                 }
 
                 mod and_root_section_has_dictionary_with_dictionary_with_no_value {
                     use super::*;
 
-                    #[test]
-                    fn then_returns_error() {
-                        let raw = r#"
+                    const RAW: &str = r#"
                             key =
                         "#;
-                        let mut p = Parser::new(raw);
+
+                    #[test]
+                    fn then_returns_error() {
+                        let mut p = Parser::new(RAW);
 
                         let actual = p.read();
 
                         assert_eq!(None, actual);
                     }
+
+                    #[test]
+                    fn then_returns_error_with_explicit_error_mode() {
+                        let mut p = Parser::new(RAW).with_empty_value(EmptyValue::Error);
+
+                        assert_eq!(None, p.read());
+                    }
+
+                    #[test]
+                    fn then_returns_empty_string_in_empty_string_mode() {
+                        let mut p = Parser::new(RAW).with_empty_value(EmptyValue::EmptyString);
+
+                        let actual = p.read().unwrap();
+
+                        assert_eq!(Some(&Value::new_string("")), actual["root"].get("key"));
+                    }
+
+                    #[test]
+                    fn then_returns_null_in_null_mode() {
+                        let mut p = Parser::new(RAW).with_empty_value(EmptyValue::Null);
+
+                        let actual = p.read().unwrap();
+
+                        assert_eq!(Some(&Value::Null), actual["root"].get("key"));
+                    }
+
+                    #[test]
+                    fn then_keeps_following_entries_in_null_mode() {
+                        let raw = "a =  # none\nb = 1\nc =\r\n";
+                        let mut p = Parser::new(raw).with_empty_value(EmptyValue::Null);
+
+                        let actual = p.read().unwrap();
+
+                        assert_eq!(Some(&Value::Null), actual["root"].get("a"));
+                        assert_eq!(Some(&Value::Integer(1)), actual["root"].get("b"));
+                        assert_eq!(Some(&Value::Null), actual["root"].get("c"));
+                    }
                 }
 
                 mod and_root_section_has_array {
@@ -981,9 +4940,57 @@ mod tests {
                     }
                 }
 
+                mod and_section_has_only_header {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_empty_section() {
+                        for raw in ["[ONLY]", "[ONLY]\n", "\n  [ONLY]  \n\n"] {
+                            let mut p = Parser::new(raw);
+
+                            let actual = p.read().unwrap();
+
+                            let mut expected = BTreeMap::new();
+                            expected.insert("ONLY".to_owned(), Section::new());
+                            assert_eq!(expected, actual);
+                        }
+                    }
+                }
+
                 mod and_section_is_duplicated {
                     use super::*;
 
+                    #[test]
+                    fn then_merges_sections_when_enabled() {
+                        let raw = r#"
+                            [SECTION]
+                            a = 1
+                            b = 1
+                            | 1col1 | 1col2 |
+                            [OTHER]
+                            [SECTION]
+                            b = 2
+                            c = 2
+                            | 2col1 |
+                        "#;
+                        let mut p = Parser::new(raw).with_merge_sections(true);
+
+                        let actual = p.read().unwrap();
+                        let section = &actual["SECTION"];
+
+                        assert_eq!(Some(&Value::Integer(1)), section.get("a"));
+                        assert_eq!(Some(&Value::Integer(2)), section.get("b"));
+                        assert_eq!(Some(&Value::Integer(2)), section.get("c"));
+                        assert_eq!(
+                            vec![
+                                vec![Value::new_string("1col1"), Value::new_string("1col2")],
+                                vec![Value::new_string("2col1")],
+                            ],
+                            section.rows
+                        );
+                        assert!(actual.contains_key("OTHER"));
+                    }
+
                     #[test]
                     fn then_returns_last_occurance_of_section() {
                         let raw = r#"
@@ -1219,6 +5226,172 @@ mod tests {
                 }
             }
 
+            mod and_ion_has_only_section_header {
+                use super::*;
+
+                mod and_section_is_accepted {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_empty_section() {
+                        for raw in ["[ACCEPTED]", "[ACCEPTED]\n", "[ACCEPTED]\n[FILTERED]\n"] {
+                            let mut p = Parser::new_filtered(raw, vec!["ACCEPTED"]);
+
+                            let actual = p.read().unwrap();
+
+                            let mut expected = BTreeMap::new();
+                            expected.insert("ACCEPTED".to_owned(), Section::new());
+                            assert_eq!(expected, actual);
+                        }
+                    }
+                }
+
+                mod and_section_is_one_of_many_accepted {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_empty_section() {
+                        let raw = "[ACCEPTED]";
+                        let mut p = Parser::new_filtered(raw, vec!["ANOTHER", "ACCEPTED"]);
+
+                        let actual = p.read().unwrap();
+
+                        let mut expected = BTreeMap::new();
+                        expected.insert("ACCEPTED".to_owned(), Section::new());
+                        assert_eq!(expected, actual);
+                    }
+                }
+
+                mod and_section_is_filtered {
+                    use super::*;
+
+                    #[test]
+                    fn then_returns_nothing() {
+                        let raw = "[FILTERED]";
+                        let mut p = Parser::new_filtered(raw, vec!["ACCEPTED"]);
+
+                        let actual = p.read().unwrap();
+
+                        assert_eq!(BTreeMap::new(), actual);
+                    }
+                }
+            }
+
+            mod and_filter_has_wildcard {
+                use super::*;
+
+                #[test]
+                fn then_returns_every_matching_section() {
+                    let raw = r#"
+                        [hotel_1]
+                        id = 1
+                        [room]
+                        id = 0
+                        [hotel_2]
+                        id = 2
+                        [contract]
+                        id = 3
+                        [contract]
+                        id = 4
+                    "#;
+                    let mut p = Parser::new_filtered(raw, vec!["hotel_*", "contract"]);
+
+                    let actual = p.read().unwrap();
+
+                    assert_eq!(
+                        vec!["contract", "hotel_1", "hotel_2"],
+                        actual.keys().collect::<Vec<_>>()
+                    );
+                    assert_eq!(Some(&Value::Integer(3)), actual["contract"].get("id"));
+                    assert_eq!(Some(&Value::Integer(2)), actual["hotel_2"].get("id"));
+                }
+
+                #[test]
+                fn then_keeps_reading_after_exact_sections_are_found() {
+                    let raw = "[contract]\n[route.eu]\n[hotel]\n[route.us]\n[route.asia]\n";
+                    let mut p = Parser::new_filtered(raw, vec!["contract", "route.*"]);
+
+                    let actual = p.read().unwrap();
+
+                    assert_eq!(
+                        vec!["contract", "route.asia", "route.eu", "route.us"],
+                        actual.keys().collect::<Vec<_>>()
+                    );
+                }
+
+                #[test]
+                fn then_exact_names_take_precedence() {
+                    let raw = "[route.eu]\na = 1\n[route.eu]\na = 2\n";
+                    let mut p = Parser::new_filtered(raw, vec!["route.eu", "route.*"]);
+
+                    let actual = p.read().unwrap();
+
+                    assert_eq!(Some(&Value::Integer(2)), actual["route.eu"].get("a"));
+                }
+
+                #[test]
+                fn then_bare_wildcard_accepts_everything() {
+                    let raw = "[a]\n[b]\n";
+                    let mut p = Parser::new_filtered(raw, vec!["*"]);
+
+                    let actual = p.read().unwrap();
+
+                    assert_eq!(vec!["a", "b"], actual.keys().collect::<Vec<_>>());
+                }
+            }
+
+            mod and_filter_is_case_insensitive {
+                use super::*;
+
+                #[test]
+                fn then_keeps_the_source_casing() {
+                    let raw = "[accepted]\na = 1\n[Another]\nb = 2\n[FILTERED]\nc = 3\n";
+                    let mut p = Parser::new_filtered(raw, vec!["ACCEPTED", "another"])
+                        .with_case_insensitive_sections(true);
+
+                    let actual = p.read().unwrap();
+
+                    assert_eq!(
+                        vec!["Another", "accepted"],
+                        actual.keys().collect::<Vec<_>>()
+                    );
+                    assert_eq!(Some(&Value::Integer(2)), actual["Another"].get("b"));
+                }
+
+                #[test]
+                fn then_stops_after_every_section_is_matched() {
+                    let raw = "[Accepted]\na = 1\n[ACCEPTED]\na = 2\n";
+                    let mut p = Parser::new_filtered(raw, vec!["accepted"])
+                        .with_case_insensitive_sections(true);
+
+                    let actual = p.read().unwrap();
+
+                    assert_eq!(vec!["Accepted"], actual.keys().collect::<Vec<_>>());
+                }
+
+                #[test]
+                fn then_wildcards_ignore_case() {
+                    let raw = "[Hotel_1]\n[HOTEL_2]\n[room]\n";
+                    let mut p = Parser::new_filtered(raw, vec!["hotel_*"])
+                        .with_case_insensitive_sections(true);
+
+                    let actual = p.read().unwrap();
+
+                    assert_eq!(
+                        vec!["HOTEL_2", "Hotel_1"],
+                        actual.keys().collect::<Vec<_>>()
+                    );
+                }
+
+                #[test]
+                fn then_case_matters_by_default() {
+                    let raw = "[accepted]\na = 1\n";
+                    let mut p = Parser::new_filtered(raw, vec!["ACCEPTED"]);
+
+                    assert!(p.read().unwrap().is_empty());
+                }
+            }
+
             mod and_ion_has_filtered_section {
                 use super::*;
 
@@ -21,6 +21,14 @@ fn test_ion() {
     assert_eq!(expected, ion.to_string());
 }
 
+#[test]
+fn test_ion_snapshot() {
+    let ion = read_ion("tests/data/test.ion");
+    let expected = fs::read_to_string("tests/expected/test.snap").unwrap();
+
+    assert_eq!(expected, ion.to_snapshot());
+}
+
 #[test]
 fn hotel_ion() {
     let ion = read_ion("tests/data/hotel.ion");
@@ -33,8 +41,7 @@ fn hotel_ion() {
 fn broken_array_and_eof() {
     let ion_err = read_err_ion("tests/data/broken_array_and_eof.ion");
 
-    let expected =
-        "ParserErrors([ParserError { lo: 55, hi: 55, desc: \"Cannot finish an array\" }])";
+    let expected = "ParserErrors([ParserError { lo: 55, hi: 55, line: 3, col: 17, desc: \"Cannot finish an array\" }])";
 
     assert_eq!(expected, ion_err.to_string());
 }
@@ -43,8 +50,7 @@ fn broken_array_and_eof() {
 fn broken_dictionary_and_eof() {
     let ion_err = read_err_ion("tests/data/broken_dictionary_and_eof.ion");
 
-    let expected =
-        "ParserErrors([ParserError { lo: 67, hi: 67, desc: \"Cannot finish a dictionary\" }])";
+    let expected = "ParserErrors([ParserError { lo: 67, hi: 67, line: 3, col: 24, desc: \"Cannot finish a dictionary\" }])";
 
     assert_eq!(expected, ion_err.to_string());
 }